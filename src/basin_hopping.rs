@@ -0,0 +1,227 @@
+//! Basin-hopping global minimization.
+//!
+//! (c) 2023 Igor Lesik
+//! MIT license
+//!
+//! References:
+//!
+//! 1. David J. Wales, Jonathan P. K. Doye - Global Optimization by Basin-Hopping and the
+//!    Lowest Energy Structures of Lennard-Jones Clusters Containing up to 110 Atoms.
+//!    J. Phys. Chem. A 101 (1997).
+//!
+use super::sampling::{Rng, SeedableRng, SplitMix64};
+
+/// Basin-hopping parameters.
+pub struct BasinHoppingOpts {
+    /// Number of basin-hopping iterations (perturbation followed by local minimization).
+    pub nr_iterations: usize,
+    /// Initial maximum displacement of a random perturbation along each coordinate.
+    pub step: f64,
+    /// Temperature of the Metropolis acceptance test; 0 accepts only downhill hops.
+    pub temperature: f64,
+    /// Acceptance rate the step size is adapted toward.
+    pub target_accept_rate: f64,
+    /// Factor by which the step size is multiplied or divided when adapting.
+    pub step_factor: f64,
+    /// Number of iterations between step size adaptations.
+    pub adapt_interval: usize,
+    /// Seed of the random perturbations, see [`SplitMix64`](crate::SplitMix64).
+    pub seed: u64,
+}
+
+impl Default for BasinHoppingOpts {
+    fn default() -> Self {
+        BasinHoppingOpts {
+            nr_iterations: 100,
+            step: 0.5,
+            temperature: 1.0,
+            target_accept_rate: 0.5,
+            step_factor: 0.9,
+            adapt_interval: 50,
+            seed: 0,
+        }
+    }
+}
+
+/// Result of basin-hopping.
+pub struct BasinHoppingRes {
+    /// Location of the best minimum found.
+    pub xmin: Vec<f64>,
+    /// Function value at `xmin`.
+    pub fmin: f64,
+    /// Number of basin-hopping iterations.
+    pub nr_iterations: usize,
+    /// Number of accepted hops.
+    pub nr_accepted: usize,
+    /// Sum of iterations reported by the local minimizer.
+    pub nr_local_iterations: usize,
+    /// Step size after adaptation.
+    pub step: f64,
+}
+
+/// Basin-hopping global minimization.
+///
+/// - David J. Wales, Jonathan P. K. Doye - Global Optimization by Basin-Hopping.
+///   J. Phys. Chem. A 101 (1997).
+///
+/// Starting from `point`, each iteration randomly displaces the current minimum by up to
+/// `step` along every coordinate, runs the `local` minimizer from the displaced point and
+/// accepts the new minimum by the Metropolis criterion `exp(-(f_new - f_old)/temperature)`.
+/// Every `adapt_interval` iterations the step size is adjusted toward `target_accept_rate`.
+/// The best minimum ever found is returned.
+///
+/// The local minimizer is called as `local(fun, x)` and must return the same
+/// `(xmin, fmin, nr_iterations)` tuple as [`amoeba`](crate::amoeba).
+///
+/// # Example
+///
+/// ```
+/// use rustamath_mnmz::{amoeba, basin_hopping, BasinHoppingOpts};
+/// use assert_float_eq::*;
+/// // Rastrigin function, global minimum 0 at the origin among many local minima.
+/// let rastrigin = |x: &[f64]| {
+///     x.iter().fold(20.0, |s, xi| s + xi*xi - 10.0*(2.0*std::f64::consts::PI*xi).cos())
+/// };
+///
+/// let opts = BasinHoppingOpts { nr_iterations: 200, step: 1.0, ..Default::default() };
+/// let res = basin_hopping(rastrigin, |f, x| amoeba(f, x, 0.1, 1.0e-10, 500), &[3.2, -2.8], &opts);
+///
+/// assert_float_absolute_eq!(res.xmin[0], 0.0, 1.0e-3);
+/// assert_float_absolute_eq!(res.xmin[1], 0.0, 1.0e-3);
+/// ```
+pub fn basin_hopping<F, L>(
+    fun: F,
+    local: L,
+    point: &[f64],
+    opts: &BasinHoppingOpts
+) -> BasinHoppingRes
+where
+    F: Fn (&[f64]) -> f64,
+    L: FnMut (&dyn Fn (&[f64]) -> f64, &[f64]) -> (Vec<f64>, f64, usize)
+{
+    let mut rng = SplitMix64::seed_from_u64(opts.seed);
+    basin_hopping_with_rng(fun, local, point, opts, &mut rng)
+}
+
+/// Basin-hopping global minimization drawing the random hops from `rng`.
+///
+/// Same as [`basin_hopping`], but `opts.seed` is ignored and the random numbers come from
+/// the given generator.
+pub fn basin_hopping_with_rng<F, L, R>(
+    fun: F,
+    mut local: L,
+    point: &[f64],
+    opts: &BasinHoppingOpts,
+    rng: &mut R
+) -> BasinHoppingRes
+where
+    F: Fn (&[f64]) -> f64,
+    L: FnMut (&dyn Fn (&[f64]) -> f64, &[f64]) -> (Vec<f64>, f64, usize),
+    R: Rng
+{
+    let adapt_interval = opts.adapt_interval.max(1);
+    let mut step = opts.step;
+
+    let (mut x, mut fx, mut nr_local_iterations) = local(&fun, point);
+    let mut xmin = x.clone();
+    let mut fmin = fx;
+
+    let mut xtrial = vec![0.0; point.len()];
+    let mut nr_accepted: usize = 0;
+    let mut nr_accepted_window: usize = 0;
+
+    for i in 1..=opts.nr_iterations {
+        // Random hop out of the current basin.
+        for (xt, xc) in xtrial.iter_mut().zip(x.iter()) {
+            *xt = xc + rng.uniform(-step, step);
+        }
+
+        let (xnew, fnew, nr_local) = local(&fun, &xtrial);
+        nr_local_iterations += nr_local;
+
+        // Metropolis acceptance test.
+        let accept = if fnew < fx {
+            true
+        }
+        else if opts.temperature > 0.0 {
+            rng.next_f64() < (-(fnew - fx) / opts.temperature).exp()
+        }
+        else {
+            false
+        };
+
+        if accept {
+            nr_accepted += 1;
+            nr_accepted_window += 1;
+            x = xnew;
+            fx = fnew;
+            if fx < fmin {
+                fmin = fx;
+                xmin.copy_from_slice(&x);
+            }
+        }
+
+        // Too many accepted hops mean the steps are too small to leave the basin.
+        if i % adapt_interval == 0 {
+            let rate = nr_accepted_window as f64 / adapt_interval as f64;
+            if rate > opts.target_accept_rate {
+                step /= opts.step_factor;
+            }
+            else {
+                step *= opts.step_factor;
+            }
+            nr_accepted_window = 0;
+        }
+    }
+
+    BasinHoppingRes {
+        xmin,
+        fmin,
+        nr_iterations: opts.nr_iterations,
+        nr_accepted,
+        nr_local_iterations,
+        step,
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_rastrigin() {
+    use super::amoeba;
+
+    let rastrigin = |x: &[f64]| {
+        x.iter().fold(20.0, |s, xi| s + xi*xi - 10.0*(2.0*std::f64::consts::PI*xi).cos())
+    };
+
+    let (_, fmin_local, _) = amoeba(rastrigin, &[3.2, -2.8], 0.1, 1.0e-10, 500);
+
+    let opts = BasinHoppingOpts { nr_iterations: 200, step: 1.0, ..Default::default() };
+    let res = basin_hopping(rastrigin, |f, x| amoeba(f, x, 0.1, 1.0e-10, 500), &[3.2, -2.8], &opts);
+
+    println!("min: {}, {} fmin: {} vs local {fmin_local} accepted: {} step: {}",
+        res.xmin[0], res.xmin[1], res.fmin, res.nr_accepted, res.step);
+
+    assert!(fmin_local > 1.0);
+    assert_float_absolute_eq!(res.xmin[0], 0.0, 1.0e-3);
+    assert_float_absolute_eq!(res.xmin[1], 0.0, 1.0e-3);
+    assert_float_absolute_eq!(res.fmin, 0.0, 1.0e-4);
+}
+
+#[cfg(test)]
+#[test]
+fn test_double_well_seed() {
+    use super::amoeba;
+
+    // Two wells, the deeper one at x = -1.
+    let double_well = |x: &[f64]| (x[0]*x[0] - 1.0).powi(2) + 0.3*x[0];
+
+    let opts = BasinHoppingOpts { nr_iterations: 50, step: 1.5, seed: 7, ..Default::default() };
+    let res1 = basin_hopping(double_well, |f, x| amoeba(f, x, 0.1, 1.0e-10, 200), &[1.0], &opts);
+    let res2 = basin_hopping(double_well, |f, x| amoeba(f, x, 0.1, 1.0e-10, 200), &[1.0], &opts);
+
+    println!("min: {} fmin: {} accepted: {}", res1.xmin[0], res1.fmin, res1.nr_accepted);
+
+    assert!(res1.xmin[0] < -0.9);
+    assert_eq!(res1.xmin, res2.xmin);
+    assert_eq!(res1.nr_accepted, res2.nr_accepted);
+}
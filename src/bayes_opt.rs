@@ -0,0 +1,570 @@
+//! Bayesian optimization with a Gaussian-process surrogate.
+//!
+//! (c) 2023 Igor Lesik
+//! MIT license
+//!
+//! References:
+//!
+//! 1. Carl E. Rasmussen, Christopher K. I. Williams - Gaussian Processes for Machine Learning.
+//!    MIT Press (2006).
+//! 2. Donald R. Jones, Matthias Schonlau, William J. Welch - Efficient Global Optimization
+//!    of Expensive Black-Box Functions. Journal of Global Optimization 13 (1998).
+//! 3. William H. Press - Numerical recipes, the art of scientific computing.
+//!    Cambridge University Press (2007).
+//!
+use super::linalg::{Matrix, cholesky, cholesky_solve, forward_subst};
+use super::sampling::{Rng, SeedableRng, SplitMix64, latin_hypercube};
+use super::simplex::amoeba;
+
+/// Covariance function of the Gaussian process.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Kernel {
+    /// Squared exponential (RBF) `s^2 exp(-r^2/2)`.
+    SquaredExponential,
+    /// Matérn with ν = 3/2, `s^2 (1 + √3 r) exp(-√3 r)`.
+    Matern32,
+    /// Matérn with ν = 5/2, `s^2 (1 + √5 r + 5r^2/3) exp(-√5 r)`.
+    Matern52,
+}
+
+impl Kernel {
+    // Correlation at the scaled distance `r`.
+    fn correlation(self, r2: f64) -> f64 {
+        match self {
+            Kernel::SquaredExponential => (-0.5 * r2).exp(),
+            Kernel::Matern32 => {
+                let r = (3.0 * r2).sqrt();
+                (1.0 + r) * (-r).exp()
+            }
+            Kernel::Matern52 => {
+                let r = (5.0 * r2).sqrt();
+                (1.0 + r + r * r / 3.0) * (-r).exp()
+            }
+        }
+    }
+}
+
+/// Acquisition function deciding where to evaluate next.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Acquisition {
+    /// Expected improvement over the best observed value.
+    ExpectedImprovement,
+    /// Lower confidence bound `mean - kappa * std` with the given `kappa`.
+    LowerConfidenceBound(f64),
+}
+
+// Bounds of the log hyperparameters; inputs are expected to be scaled to about unit size
+// and outputs are standardized.
+const LOG_LENGTH_SCALE_RANGE: (f64, f64) = (-4.6, 4.6); // 0.01 .. 100
+const LOG_SIGNAL_VARIANCE_RANGE: (f64, f64) = (-4.6, 4.6);
+const LOG_NOISE_VARIANCE_RANGE: (f64, f64) = (-18.4, 0.0); // 1e-8 .. 1
+
+/// Gaussian-process regression model.
+///
+/// - Carl E. Rasmussen, Christopher K. I. Williams - Gaussian Processes for Machine Learning.
+///   MIT Press (2006).
+///
+/// Observations are standardized internally. The kernel has one length scale per input
+/// dimension, a signal variance and a noise variance; [`GaussianProcess::fit`] chooses them by
+/// maximizing the log marginal likelihood with [`amoeba`](crate::amoeba).
+#[derive(Clone)]
+pub struct GaussianProcess {
+    /// Covariance function.
+    pub kernel: Kernel,
+    /// Length scale along every input dimension.
+    pub length_scales: Vec<f64>,
+    /// Signal variance of the standardized observations.
+    pub signal_variance: f64,
+    /// Noise variance of the standardized observations.
+    pub noise_variance: f64,
+    xs: Vec<Vec<f64>>,
+    y_mean: f64,
+    y_std: f64,
+    chol: Matrix,
+    alpha: Vec<f64>,
+    log_likelihood: f64,
+}
+
+impl GaussianProcess {
+    /// New model with `ndim` inputs and default hyperparameters.
+    pub fn new(kernel: Kernel, ndim: usize) -> Self {
+        GaussianProcess {
+            kernel,
+            length_scales: vec![0.3; ndim],
+            signal_variance: 1.0,
+            noise_variance: 1.0e-6,
+            xs: Vec::new(),
+            y_mean: 0.0,
+            y_std: 1.0,
+            chol: Matrix::new(0, 0),
+            alpha: Vec::new(),
+            log_likelihood: 0.0,
+        }
+    }
+
+    /// Covariance of two inputs.
+    pub fn covariance(&self, x1: &[f64], x2: &[f64]) -> f64 {
+        let r2 = x1.iter().zip(x2.iter()).zip(self.length_scales.iter())
+            .map(|((a, b), l)| ((a - b) / l) * ((a - b) / l))
+            .sum::<f64>();
+        self.signal_variance * self.kernel.correlation(r2)
+    }
+
+    /// Condition the model on observations `ys` at `xs`, keeping the hyperparameters.
+    ///
+    /// Returns `false` if the covariance matrix is numerically singular.
+    pub fn set_data(&mut self, xs: &[Vec<f64>], ys: &[f64]) -> bool {
+        let n = ys.len();
+        self.xs = xs.to_vec();
+
+        self.y_mean = ys.iter().sum::<f64>() / n.max(1) as f64;
+        let var = ys.iter().map(|y| (y - self.y_mean) * (y - self.y_mean)).sum::<f64>() / n.max(1) as f64;
+        self.y_std = if var > 0.0 { var.sqrt() } else { 1.0 };
+
+        let mut k = Matrix::new(n, n);
+        for i in 0..n {
+            for j in 0..=i {
+                let kij = self.covariance(&xs[i], &xs[j]);
+                k.set(i, j, kij);
+                k.set(j, i, kij);
+            }
+            let kii = k.get(i, i) + self.noise_variance;
+            k.set(i, i, kii);
+        }
+
+        let chol = match cholesky(&k) {
+            Some(l) => l,
+            None => return false,
+        };
+
+        let y: Vec<f64> = ys.iter().map(|y| (y - self.y_mean) / self.y_std).collect();
+        let mut alpha = y.clone();
+        cholesky_solve(&chol, &mut alpha);
+
+        // log p(y|X) = -y^T K^-1 y / 2 - log|K| / 2 - n log(2 Pi) / 2
+        let log_det: f64 = (0..n).map(|i| chol.get(i, i).ln()).sum();
+        let fit: f64 = y.iter().zip(alpha.iter()).map(|(a, b)| a * b).sum();
+        self.log_likelihood = -0.5 * fit - log_det - 0.5 * n as f64 * (2.0 * std::f64::consts::PI).ln();
+
+        self.chol = chol;
+        self.alpha = alpha;
+        true
+    }
+
+    /// Fit the hyperparameters by maximizing the log marginal likelihood of the observations
+    /// and condition the model on them.
+    pub fn fit(&mut self, xs: &[Vec<f64>], ys: &[f64]) {
+        let ndim = self.length_scales.len();
+
+        let nll = |theta: &[f64]| {
+            let mut gp = self.clone();
+            gp.set_hyperparameters(theta);
+            if gp.set_data(xs, ys) { -gp.log_likelihood } else { f64::MAX }
+        };
+
+        // Restart from the current and from the default hyperparameters.
+        let mut starts = vec![self.hyperparameters()];
+        let mut default = vec![0.3_f64.ln(); ndim];
+        default.extend_from_slice(&[0.0, 1.0e-6_f64.ln()]);
+        starts.push(default);
+
+        let mut best = (starts[0].clone(), f64::MAX);
+        for start in starts {
+            let (theta, f, _) = amoeba(nll, &start, 1.0, 1.0e-6, 200);
+            if f < best.1 {
+                best = (theta, f);
+            }
+        }
+
+        self.set_hyperparameters(&best.0);
+        if !self.set_data(xs, ys) {
+            // Fall back to a noisier model that can always be factored.
+            self.noise_variance = LOG_NOISE_VARIANCE_RANGE.1.exp();
+            self.set_data(xs, ys);
+        }
+    }
+
+    /// Posterior mean and standard deviation of the latent function at `x`.
+    pub fn predict(&self, x: &[f64]) -> (f64, f64) {
+        if self.xs.is_empty() {
+            return (self.y_mean, self.signal_variance.sqrt() * self.y_std);
+        }
+        let mut k: Vec<f64> = self.xs.iter().map(|xi| self.covariance(x, xi)).collect();
+        let mean: f64 = k.iter().zip(self.alpha.iter()).map(|(a, b)| a * b).sum();
+        forward_subst(&self.chol, &mut k);
+        let var = self.signal_variance - k.iter().map(|v| v * v).sum::<f64>();
+        (self.y_mean + self.y_std * mean, self.y_std * var.max(0.0).sqrt())
+    }
+
+    /// Log marginal likelihood of the standardized observations.
+    pub fn log_marginal_likelihood(&self) -> f64 {
+        self.log_likelihood
+    }
+
+    fn hyperparameters(&self) -> Vec<f64> {
+        let mut theta: Vec<f64> = self.length_scales.iter().map(|l| l.ln()).collect();
+        theta.push(self.signal_variance.ln());
+        theta.push(self.noise_variance.ln());
+        theta
+    }
+
+    fn set_hyperparameters(&mut self, theta: &[f64]) {
+        let ndim = self.length_scales.len();
+        for (l, t) in self.length_scales.iter_mut().zip(theta.iter()) {
+            *l = t.clamp(LOG_LENGTH_SCALE_RANGE.0, LOG_LENGTH_SCALE_RANGE.1).exp();
+        }
+        self.signal_variance = theta[ndim].clamp(LOG_SIGNAL_VARIANCE_RANGE.0, LOG_SIGNAL_VARIANCE_RANGE.1).exp();
+        self.noise_variance = theta[ndim+1].clamp(LOG_NOISE_VARIANCE_RANGE.0, LOG_NOISE_VARIANCE_RANGE.1).exp();
+    }
+}
+
+/// Complementary error function with fractional error less than 1.2e-7.
+///
+/// See book "Numerical recipes in C, 2nd edition", section 6.2.
+fn erfc(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1.0 / (1.0 + 0.5 * z);
+    let ans = t * (-z*z - 1.26551223 + t*(1.00002368 + t*(0.37409196 + t*(0.09678418
+        + t*(-0.18628806 + t*(0.27886807 + t*(-1.13520398 + t*(1.48851587
+        + t*(-0.82215223 + t*0.17087277))))))))).exp();
+    if x >= 0.0 { ans } else { 2.0 - ans }
+}
+
+// Standard normal probability density and cumulative distribution.
+fn normal_pdf(z: f64) -> f64 {
+    (-0.5 * z * z).exp() / (2.0 * std::f64::consts::PI).sqrt()
+}
+
+fn normal_cdf(z: f64) -> f64 {
+    0.5 * erfc(-z / std::f64::consts::SQRT_2)
+}
+
+/// Number of random candidates per dimension the acquisition is sampled at.
+const NR_CANDIDATES: usize = 500;
+
+/// Number of best candidates refined by the downhill simplex.
+const NR_REFINED: usize = 3;
+
+/// Bayesian optimization with an ask-and-tell interface.
+///
+/// - Donald R. Jones, Matthias Schonlau, William J. Welch - Efficient Global Optimization
+///   of Expensive Black-Box Functions (1998).
+///
+/// A Gaussian process is fitted to the observations and the next points to evaluate
+/// are the minima of the acquisition function over the box `lower..upper`.
+/// The first points come from a Latin hypercube design until there are enough observations
+/// for a model.
+/// A batch of points asked at once is built by the "kriging believer" heuristic: points
+/// asked but not told yet are assumed to have the value the model predicts.
+///
+/// # Example
+///
+/// ```
+/// use rustamath_mnmz::{BayesOpt, Kernel, Acquisition};
+/// let fun = |x: &[f64]| (x[0] - 0.3)*(x[0] - 0.3) + (x[1] + 0.5)*(x[1] + 0.5);
+///
+/// let mut opt = BayesOpt::new(&[-2.0, -2.0], &[2.0, 2.0], Kernel::Matern52,
+///     Acquisition::ExpectedImprovement, 1);
+///
+/// for _ in 0..10 {
+///     // Ask for three points, evaluate them (maybe in parallel) and tell the results.
+///     for x in opt.ask(3) {
+///         let y = fun(&x);
+///         opt.tell(&x, y);
+///     }
+/// }
+///
+/// let (xmin, fmin) = opt.best().unwrap();
+/// println!("min: {}, {} fmin: {fmin}", xmin[0], xmin[1]);
+/// assert!(fmin < 1.0e-2);
+/// ```
+pub struct BayesOpt<R: Rng = SplitMix64> {
+    lower: Vec<f64>,
+    upper: Vec<f64>,
+    acquisition: Acquisition,
+    gp: GaussianProcess,
+    // Initial design not handed out yet.
+    initial: Vec<Vec<f64>>,
+    // Observations and pending points in the unit hypercube.
+    xs: Vec<Vec<f64>>,
+    ys: Vec<f64>,
+    pending: Vec<Vec<f64>>,
+    nr_fitted: usize,
+    rng: R,
+}
+
+impl BayesOpt {
+    /// New optimizer over the box `lower[i] <= x[i] <= upper[i]`.
+    pub fn new(
+        lower: &[f64],
+        upper: &[f64],
+        kernel: Kernel,
+        acquisition: Acquisition,
+        seed: u64
+    ) -> Self
+    {
+        BayesOpt::with_rng(lower, upper, kernel, acquisition, SplitMix64::seed_from_u64(seed))
+    }
+}
+
+impl<R: Rng> BayesOpt<R> {
+    /// New optimizer over the box `lower[i] <= x[i] <= upper[i]` drawing its random
+    /// numbers from `rng`.
+    pub fn with_rng(
+        lower: &[f64],
+        upper: &[f64],
+        kernel: Kernel,
+        acquisition: Acquisition,
+        mut rng: R
+    ) -> Self
+    {
+        let ndim = lower.len();
+        let mut initial = latin_hypercube((ndim + 1).max(4), ndim, &mut rng);
+        initial.reverse();
+        BayesOpt {
+            lower: lower.to_vec(),
+            upper: upper.to_vec(),
+            acquisition,
+            gp: GaussianProcess::new(kernel, ndim),
+            initial,
+            xs: Vec::new(),
+            ys: Vec::new(),
+            pending: Vec::new(),
+            nr_fitted: 0,
+            rng,
+        }
+    }
+
+    /// Points to evaluate next.
+    pub fn ask(&mut self, n: usize) -> Vec<Vec<f64>> {
+        let mut batch = Vec::with_capacity(n);
+
+        for _ in 0..n {
+            let u = if let Some(u) = self.initial.pop() {
+                u
+            }
+            else if self.xs.len() < 2 {
+                self.random_point()
+            }
+            else {
+                if self.nr_fitted != self.xs.len() {
+                    self.gp.fit(&self.xs, &self.ys);
+                    self.nr_fitted = self.xs.len();
+                }
+                self.next_point()
+            };
+            batch.push(self.unit_to_box(&u));
+            self.pending.push(u);
+        }
+
+        batch
+    }
+
+    /// Report the value `y` of the function at `x`.
+    pub fn tell(&mut self, x: &[f64], y: f64) {
+        let u = self.box_to_unit(x);
+        if let Some(i) = self.pending.iter().position(|p| p.iter().zip(u.iter()).all(|(a, b)| (a - b).abs() < 1.0e-12)) {
+            self.pending.swap_remove(i);
+        }
+        self.xs.push(u);
+        self.ys.push(y);
+    }
+
+    /// Best observation so far.
+    pub fn best(&self) -> Option<(Vec<f64>, f64)> {
+        let i = (0..self.ys.len()).min_by(|&a, &b| self.ys[a].total_cmp(&self.ys[b]))?;
+        Some((self.unit_to_box(&self.xs[i]), self.ys[i]))
+    }
+
+    /// Number of observations told so far.
+    pub fn nr_observations(&self) -> usize {
+        self.ys.len()
+    }
+
+    /// Surrogate model of the last fit.
+    pub fn model(&self) -> &GaussianProcess {
+        &self.gp
+    }
+
+    fn box_to_unit(&self, x: &[f64]) -> Vec<f64> {
+        (0..x.len()).map(|i| (x[i] - self.lower[i]) / (self.upper[i] - self.lower[i])).collect()
+    }
+
+    fn unit_to_box(&self, u: &[f64]) -> Vec<f64> {
+        (0..u.len()).map(|i| self.lower[i] + u[i] * (self.upper[i] - self.lower[i])).collect()
+    }
+
+    fn random_point(&mut self) -> Vec<f64> {
+        (0..self.lower.len()).map(|_| self.rng.next_f64()).collect()
+    }
+
+    // Minimize the acquisition of the model conditioned on the observations
+    // and on the predicted values at the pending points.
+    fn next_point(&mut self) -> Vec<f64> {
+        let ndim = self.lower.len();
+
+        let mut gp = self.gp.clone();
+        if !self.pending.is_empty() {
+            let mut xs = self.xs.clone();
+            let mut ys = self.ys.clone();
+            for p in &self.pending {
+                xs.push(p.clone());
+                ys.push(self.gp.predict(p).0);
+            }
+            if !gp.set_data(&xs, &ys) {
+                gp = self.gp.clone();
+            }
+        }
+
+        let fbest = self.ys.iter().copied().fold(f64::INFINITY, f64::min);
+        let acquisition = self.acquisition;
+        let acq = |u: &[f64]| {
+            let u: Vec<f64> = u.iter().map(|ui| ui.clamp(0.0, 1.0)).collect();
+            let (mean, std) = gp.predict(&u);
+            match acquisition {
+                Acquisition::ExpectedImprovement => {
+                    if std <= 0.0 {
+                        return 0.0;
+                    }
+                    let z = (fbest - mean) / std;
+                    -((fbest - mean) * normal_cdf(z) + std * normal_pdf(z))
+                }
+                Acquisition::LowerConfidenceBound(kappa) => mean - kappa * std,
+            }
+        };
+
+        let mut candidates: Vec<(f64, Vec<f64>)> = (0..NR_CANDIDATES * ndim)
+            .map(|_| {
+                let u = self.random_point();
+                (acq(&u), u)
+            })
+            .collect();
+        candidates.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        let mut best = candidates[0].clone();
+        for (_, u0) in candidates.iter().take(NR_REFINED) {
+            let (u, a, _) = amoeba(acq, u0, 0.05, 1.0e-8, 200);
+            if a < best.0 {
+                best = (a, u);
+            }
+        }
+
+        best.1.iter().map(|ui| ui.clamp(0.0, 1.0)).collect()
+    }
+}
+
+/// Bayesian optimization of an expensive function over a box.
+///
+/// Runs [`BayesOpt`] one point at a time for `max_evaluations` evaluations (20 if 0).
+///
+/// Returned is the best point, the function value there and the number of evaluations.
+///
+/// # Example
+///
+/// ```
+/// use rustamath_mnmz::{bayes_opt, Kernel, Acquisition};
+/// let fun = |x: &[f64]| (x[0] - 1.0).powi(2) + 2.0;
+///
+/// let (xmin, fmin, _) = bayes_opt(fun, &[-4.0], &[4.0], Kernel::SquaredExponential,
+///     Acquisition::LowerConfidenceBound(2.0), 15, 0);
+///
+/// println!("xmin: {} fmin: {fmin}", xmin[0]);
+/// assert!((xmin[0] - 1.0).abs() < 0.1);
+/// ```
+pub fn bayes_opt<F: Fn (&[f64]) -> f64>(
+    fun: F,
+    lower: &[f64],
+    upper: &[f64],
+    kernel: Kernel,
+    acquisition: Acquisition,
+    max_evaluations: usize,
+    seed: u64
+) -> (Vec<f64>, f64, usize)
+{
+    let max_evaluations = if max_evaluations < 1 { 20 } else { max_evaluations };
+
+    let mut opt = BayesOpt::new(lower, upper, kernel, acquisition, seed);
+    for _ in 0..max_evaluations {
+        let x = opt.ask(1).remove(0);
+        let y = fun(&x);
+        opt.tell(&x, y);
+    }
+
+    let (xmin, fmin) = opt.best().unwrap_or((Vec::new(), f64::NAN));
+    (xmin, fmin, opt.nr_observations())
+}
+
+#[cfg(test)]
+#[test]
+fn test_gp_interpolation() {
+    let xs: Vec<Vec<f64>> = (0..8).map(|i| vec![i as f64 / 7.0]).collect();
+    let ys: Vec<f64> = xs.iter().map(|x| (6.0 * x[0]).sin()).collect();
+
+    for kernel in [Kernel::SquaredExponential, Kernel::Matern32, Kernel::Matern52] {
+        let mut gp = GaussianProcess::new(kernel, 1);
+        let mut gp_default = gp.clone();
+        gp_default.set_data(&xs, &ys);
+        gp.fit(&xs, &ys);
+
+        let (mean, std) = gp.predict(&[0.5]);
+        let (mean_at, std_at) = gp.predict(&xs[3]);
+
+        println!("{:?} length scale: {} signal: {} noise: {} mean: {mean} std: {std} log likelihood: {} vs {}",
+            kernel, gp.length_scales[0], gp.signal_variance, gp.noise_variance,
+            gp.log_marginal_likelihood(), gp_default.log_marginal_likelihood());
+
+        assert!(gp.log_marginal_likelihood() >= gp_default.log_marginal_likelihood());
+        assert_float_absolute_eq!(mean, 3.0_f64.sin(), 2.0e-2);
+        assert_float_absolute_eq!(mean_at, ys[3], 1.0e-3);
+        assert!(std_at < std);
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_normal_cdf() {
+    assert_float_absolute_eq!(normal_cdf(0.0), 0.5, 1.0e-7);
+    assert_float_absolute_eq!(normal_cdf(1.0), 0.8413447, 1.0e-6);
+    assert_float_absolute_eq!(normal_cdf(-1.96), 0.0249979, 1.0e-6);
+}
+
+#[cfg(test)]
+#[test]
+fn test_branin() {
+    let branin = |x: &[f64]| {
+        let pi = std::f64::consts::PI;
+        let (a, b, c) = (1.0, 5.1/(4.0*pi*pi), 5.0/pi);
+        let (r, s, t) = (6.0, 10.0, 1.0/(8.0*pi));
+        a*(x[1] - b*x[0]*x[0] + c*x[0] - r).powi(2) + s*(1.0 - t)*x[0].cos() + s
+    };
+
+    for acquisition in [Acquisition::ExpectedImprovement, Acquisition::LowerConfidenceBound(2.0)] {
+        let (xmin, fmin, nr_evaluations) = bayes_opt(branin, &[-5.0, 0.0], &[10.0, 15.0],
+            Kernel::Matern52, acquisition, 40, 3);
+
+        println!("{:?} min: {}, {} fmin: {fmin} evaluations: {nr_evaluations}", acquisition, xmin[0], xmin[1]);
+
+        assert_eq!(nr_evaluations, 40);
+        assert!(fmin < 0.45);
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_ask_batch() {
+    let mut opt = BayesOpt::new(&[0.0], &[1.0], Kernel::Matern52, Acquisition::ExpectedImprovement, 5);
+
+    let first = opt.ask(4);
+    for x in &first {
+        opt.tell(x, (x[0] - 0.7).powi(2));
+    }
+
+    let batch = opt.ask(3);
+    assert_eq!(batch.len(), 3);
+    for (i, a) in batch.iter().enumerate() {
+        assert!(a[0] >= 0.0 && a[0] <= 1.0);
+        for b in batch.iter().skip(i + 1) {
+            assert!((a[0] - b[0]).abs() > 1.0e-6);
+        }
+    }
+}
@@ -0,0 +1,51 @@
+//! Simple bounds `lower[i] <= x[i] <= upper[i]` on the variables.
+//!
+//! (c) 2023 Igor Lesik
+//! MIT license
+//!
+
+/// Which bound, if any, a variable sits on at the solution.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ActiveBound {
+    /// The variable is strictly inside its bounds.
+    Free,
+    /// The variable is at its lower bound.
+    Lower,
+    /// The variable is at its upper bound.
+    Upper,
+}
+
+/// Relative distance to a bound under which the bound counts as active.
+const ACTIVE_TOLERANCE: f64 = 1.0e-8_f64;
+
+/// Project `x` onto the box `lower..upper`.
+pub fn project(x: &mut [f64], lower: &[f64], upper: &[f64]) {
+    for ((xi, lo), hi) in x.iter_mut().zip(lower.iter()).zip(upper.iter()) {
+        *xi = xi.max(*lo).min(*hi);
+    }
+}
+
+/// Active bounds of the point `x` in the box `lower..upper`; infinite bounds are never active.
+///
+/// # Example
+///
+/// ```
+/// use rustamath_mnmz::bounds::{active_bounds, ActiveBound};
+/// let active = active_bounds(&[0.0, 0.5, 1.0], &[0.0; 3], &[1.0; 3]);
+/// assert_eq!(active, vec![ActiveBound::Lower, ActiveBound::Free, ActiveBound::Upper]);
+/// ```
+pub fn active_bounds(x: &[f64], lower: &[f64], upper: &[f64]) -> Vec<ActiveBound> {
+    x.iter().zip(lower.iter()).zip(upper.iter())
+        .map(|((&xi, &lo), &hi)| {
+            if lo.is_finite() && xi - lo <= ACTIVE_TOLERANCE * (1.0 + lo.abs()) {
+                ActiveBound::Lower
+            }
+            else if hi.is_finite() && hi - xi <= ACTIVE_TOLERANCE * (1.0 + hi.abs()) {
+                ActiveBound::Upper
+            }
+            else {
+                ActiveBound::Free
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,356 @@
+//! Constrained optimization by linear approximations (COBYLA).
+//!
+//! (c) 2023 Igor Lesik
+//! MIT license
+//!
+//! References:
+//!
+//! 1. M. J. D. Powell - A direct search optimization method that models the objective and
+//!    constraint functions by linear interpolation.
+//!    Advances in Optimization and Numerical Analysis, Kluwer (1994).
+//! 2. M. J. D. Powell - A view of algorithms for optimization without derivatives.
+//!    Mathematics Today 43 (2007).
+//!
+use super::linalg::{Matrix, invert};
+use super::lp::{linprog, LpStatus};
+
+/// Result of COBYLA.
+pub struct CobylaRes {
+    /// Best point found by the merit function.
+    pub xmin: Vec<f64>,
+    /// Objective value at `xmin`.
+    pub fmin: f64,
+    /// Largest constraint value `max(g_j(xmin))` if positive, 0 if `xmin` is feasible.
+    pub violation: f64,
+    /// Final trust-region radius, equal to `rhoend` unless the evaluation limit was reached.
+    pub rho: f64,
+    /// Number of evaluations of the objective and constraints.
+    pub nr_evaluations: usize,
+}
+
+/// Constrained optimization by linear approximations (COBYLA).
+///
+/// - M. J. D. Powell - A direct search optimization method that models the objective and
+///   constraint functions by linear interpolation (1994).
+///
+/// Minimizes `fun(x)` subject to `g(x) <= 0` without derivatives. The callback `cons(x, g)`
+/// fills the `m` constraint values.
+///
+/// The objective and every constraint are interpolated by linear functions on a simplex of
+/// `n + 1` points. Each iteration minimizes the linear objective model subject to the linear
+/// constraint models inside the trust region `|d_i| <= rho` with [`linprog`](crate::linprog),
+/// first reducing the predicted violation as far as the region allows. Trial points are
+/// judged by the merit function `f + sigma max(0, max g)`, with the penalty `sigma` kept above
+/// the Lagrange multipliers of the model. When steps stop making progress and the simplex is
+/// well shaped, `rho` is halved, from `rhobeg` down to `rhoend`; a badly shaped simplex is
+/// first repaired by a geometry step that moves one vertex away from the opposite face.
+///
+/// `rhobeg` should be about a tenth of the expected change of the variables, `rhoend` is the
+/// required accuracy. At most `max_evaluations` evaluations are made (1000 if 0).
+///
+/// # Example
+///
+/// ```
+/// use rustamath_mnmz::cobyla;
+/// use assert_float_eq::*;
+/// // minimize x y  subject to  x^2 + y^2 <= 1
+/// let res = cobyla(
+///     |x: &[f64]| x[0] * x[1],
+///     |x: &[f64], g: &mut [f64]| g[0] = x[0]*x[0] + x[1]*x[1] - 1.0, 1,
+///     &[1.0, 1.0], 0.5, 1.0e-6, 0);
+///
+/// assert_float_absolute_eq!(res.fmin, -0.5, 1.0e-6);
+/// assert_float_absolute_eq!(res.xmin[0], -res.xmin[1], 1.0e-5);
+/// assert!(res.violation < 1.0e-6);
+/// ```
+#[allow(clippy::needless_range_loop)]
+pub fn cobyla<F, C>(
+    fun: F,
+    cons: C,
+    m: usize,
+    point: &[f64],
+    rhobeg: f64,
+    rhoend: f64,
+    max_evaluations: usize
+) -> CobylaRes
+where
+    F: Fn (&[f64]) -> f64,
+    C: Fn (&[f64], &mut [f64])
+{
+    let max_evaluations = if max_evaluations < 1 { 1000 } else { max_evaluations };
+    let rhoend = rhoend.min(rhobeg);
+    let n = point.len();
+
+    let evaluate = |x: &[f64], nr_evaluations: &mut usize| {
+        *nr_evaluations += 1;
+        let mut g = vec![0.0; m];
+        cons(x, &mut g);
+        (fun(x), g)
+    };
+    let violation = |g: &[f64]| g.iter().fold(0.0_f64, |v, &gj| v.max(gj));
+    let dot = |a: &[f64], b: &[f64]| a.iter().zip(b.iter()).map(|(x, y)| x * y).sum::<f64>();
+
+    // Simplex vertices with their function and constraint values, the best one first.
+    let mut nr_evaluations: usize = 0;
+    let mut pts: Vec<Vec<f64>> = Vec::with_capacity(n + 1);
+    let mut fs: Vec<f64> = Vec::with_capacity(n + 1);
+    let mut gs: Vec<Vec<f64>> = Vec::with_capacity(n + 1);
+    for j in 0..=n {
+        let mut x = point.to_vec();
+        if j > 0 {
+            x[j - 1] += rhobeg;
+        }
+        let (f, g) = evaluate(&x, &mut nr_evaluations);
+        pts.push(x);
+        fs.push(f);
+        gs.push(g);
+    }
+
+    let mut rho = rhobeg;
+    let mut sigma = 0.0;
+    let mut need_geometry = false;
+
+    while nr_evaluations < max_evaluations {
+        let merit = |f: f64, g: &[f64], sigma: f64| f + sigma * violation(g);
+        let best = (0..=n)
+            .min_by(|&a, &b| merit(fs[a], &gs[a], sigma).total_cmp(&merit(fs[b], &gs[b], sigma)))
+            .unwrap_or(0);
+        pts.swap(0, best);
+        fs.swap(0, best);
+        gs.swap(0, best);
+
+        // Rows of D are the edges from the best vertex; the linear model gradient `a` of
+        // values `v` solves D a = v - v0, and column j of D^-1 is the gradient of the
+        // barycentric coordinate of vertex j.
+        let mut dmat = Matrix::new(n, n);
+        for j in 0..n {
+            for i in 0..n {
+                dmat.set(j, i, pts[j + 1][i] - pts[0][i]);
+            }
+        }
+        let Some(dinv) = invert(&dmat) else { break };
+        let model = |dv: &dyn Fn (usize) -> f64| -> Vec<f64> {
+            (0..n).map(|i| (0..n).map(|j| dinv.get(i, j) * dv(j)).sum()).collect()
+        };
+        let a = model(&|j| fs[j + 1] - fs[0]);
+        let b: Vec<Vec<f64>> = (0..m).map(|k| model(&|j| gs[j + 1][k] - gs[0][k])).collect();
+
+        let model_violation = |d: &[f64]| {
+            (0..m).fold(0.0_f64, |v, k| v.max(gs[0][k] + dot(&b[k], d)))
+        };
+
+        // Simplex is acceptable when every edge is at most 2 rho long and every vertex is at
+        // least rho/4 away from its opposite face.
+        let edge: Vec<f64> = (0..n)
+            .map(|j| pts[j + 1].iter().zip(pts[0].iter()).map(|(x, x0)| (x - x0).powi(2)).sum::<f64>().sqrt())
+            .collect();
+        let face: Vec<f64> = (0..n)
+            .map(|j| 1.0 / (0..n).map(|i| dinv.get(i, j).powi(2)).sum::<f64>().sqrt())
+            .collect();
+        let acceptable = edge.iter().all(|&e| e <= 2.0 * rho) && face.iter().all(|&s| s >= 0.25 * rho);
+
+        if need_geometry {
+            need_geometry = false;
+            // Replace the farthest vertex, or the one closest to its opposite face, by a point
+            // at distance rho from the best vertex along the normal of that face.
+            let far = (0..n).max_by(|&x, &y| edge[x].total_cmp(&edge[y])).unwrap_or(0);
+            let j = if edge[far] > 2.0 * rho {
+                far
+            }
+            else {
+                (0..n).min_by(|&x, &y| face[x].total_cmp(&face[y])).unwrap_or(0)
+            };
+            let mut d: Vec<f64> = (0..n).map(|i| rho * face[j] * dinv.get(i, j)).collect();
+            let minus: Vec<f64> = d.iter().map(|di| -di).collect();
+            if dot(&a, &minus) + sigma * model_violation(&minus) < dot(&a, &d) + sigma * model_violation(&d) {
+                d = minus;
+            }
+            let y: Vec<f64> = pts[0].iter().zip(d.iter()).map(|(x, di)| x + di).collect();
+            let (fy, gy) = evaluate(&y, &mut nr_evaluations);
+            pts[j + 1] = y;
+            fs[j + 1] = fy;
+            gs[j + 1] = gy;
+            continue;
+        }
+
+        // Trust-region subproblem in the shifted variables u = d + rho >= 0, u <= 2 rho.
+        // First the least violation t reachable within the region, then the least objective
+        // with the violation kept at t.
+        let shift: Vec<f64> = b.iter().map(|bk| rho * bk.iter().sum::<f64>()).collect();
+        let mut box_rows: Vec<Vec<f64>> = Vec::with_capacity(n);
+        for i in 0..n {
+            let mut row = vec![0.0; n];
+            row[i] = 1.0;
+            box_rows.push(row);
+        }
+
+        let mut t = 0.0;
+        if m > 0 {
+            let mut c1 = vec![0.0; n + 1];
+            c1[n] = 1.0;
+            let mut a1: Vec<Vec<f64>> = Vec::with_capacity(m + n);
+            let mut b1: Vec<f64> = Vec::with_capacity(m + n);
+            for k in 0..m {
+                a1.push(b[k].iter().copied().chain(std::iter::once(-1.0)).collect());
+                b1.push(shift[k] - gs[0][k]);
+            }
+            for row in box_rows.iter() {
+                a1.push(row.iter().copied().chain(std::iter::once(0.0)).collect());
+                b1.push(2.0 * rho);
+            }
+            let lp = linprog(&c1, &a1, &b1, &[], &[], 0);
+            if lp.status == LpStatus::Optimal {
+                t = lp.fun.max(0.0);
+            }
+        }
+
+        let mut a2: Vec<Vec<f64>> = b.clone();
+        let mut b2: Vec<f64> = (0..m).map(|k| shift[k] - gs[0][k] + t + 1.0e-10 * (1.0 + t)).collect();
+        a2.extend(box_rows);
+        b2.extend(std::iter::repeat_n(2.0 * rho, n));
+        let lp = linprog(&a, &a2, &b2, &[], &[], 0);
+        if lp.status != LpStatus::Optimal {
+            break;
+        }
+        let d: Vec<f64> = lp.x.iter().map(|u| u - rho).collect();
+
+        // Penalty above the model multipliers, and high enough for the step to reduce the
+        // merit model when it reduces the violation.
+        let mut sigma_new = sigma;
+        let max_multiplier = lp.duals_ub[..m].iter().fold(0.0_f64, |s, l| s.max(-l));
+        if sigma_new < 1.5 * max_multiplier {
+            sigma_new = 2.0 * max_multiplier;
+        }
+        let fred = -dot(&a, &d);
+        let vred = violation(&gs[0]) - model_violation(&d);
+        if vred > 0.0 && fred + sigma_new * vred <= 0.0 {
+            sigma_new = 2.0 * (-fred / vred);
+        }
+        if sigma_new > sigma {
+            sigma = sigma_new;
+            let m0 = merit(fs[0], &gs[0], sigma);
+            if (1..=n).any(|j| merit(fs[j], &gs[j], sigma) < m0) {
+                continue;
+            }
+        }
+        let predicted = fred + sigma * vred;
+
+        let dnorm = d.iter().fold(0.0_f64, |s, di| s.max(di.abs()));
+        if dnorm < 0.5 * rho || predicted <= 0.0 {
+            if !acceptable {
+                need_geometry = true;
+            }
+            else if rho <= rhoend {
+                break;
+            }
+            else {
+                rho = reduce_rho(rho, rhoend);
+            }
+            continue;
+        }
+
+        let y: Vec<f64> = pts[0].iter().zip(d.iter()).map(|(x, di)| x + di).collect();
+        let (fy, gy) = evaluate(&y, &mut nr_evaluations);
+        let actual = merit(fs[0], &gs[0], sigma) - merit(fy, &gy, sigma);
+        let ratio = actual / predicted;
+
+        // The new point replaces the vertex that leaves the largest simplex, where the volume
+        // changes by the factor |w_j| of the barycentric coordinate of d, weighted toward far
+        // vertices. A worse point is only taken if it does not flatten the simplex.
+        let score: Vec<f64> = (0..n)
+            .map(|j| (0..n).map(|i| dinv.get(i, j) * d[i]).sum::<f64>().abs() * (edge[j] / rho).max(1.0))
+            .collect();
+        let j = (0..n).max_by(|&x, &y| score[x].total_cmp(&score[y])).unwrap_or(0);
+        if actual > 0.0 || score[j] >= 1.0 {
+            pts[j + 1] = y;
+            fs[j + 1] = fy;
+            gs[j + 1] = gy;
+        }
+
+        if ratio < 0.1 {
+            if !acceptable {
+                need_geometry = true;
+            }
+            else if actual <= 0.0 {
+                if rho <= rhoend {
+                    break;
+                }
+                rho = reduce_rho(rho, rhoend);
+            }
+        }
+    }
+
+    let merit = |j: usize| fs[j] + sigma * violation(&gs[j]);
+    let best = (0..=n).min_by(|&a, &b| merit(a).total_cmp(&merit(b))).unwrap_or(0);
+
+    CobylaRes {
+        violation: violation(&gs[best]),
+        xmin: pts.swap_remove(best),
+        fmin: fs[best],
+        rho,
+        nr_evaluations,
+    }
+}
+
+fn reduce_rho(rho: f64, rhoend: f64) -> f64 {
+    let rho = 0.5 * rho;
+    if rho <= 1.5 * rhoend { rhoend } else { rho }
+}
+
+#[cfg(test)]
+#[test]
+fn test_two_constraints() {
+    // minimize (x - 2)^2 + (y - 1)^2  subject to  x^2 <= y,  x + y <= 2, solution (1, 1)
+    let res = cobyla(
+        |x: &[f64]| (x[0] - 2.0).powi(2) + (x[1] - 1.0).powi(2),
+        |x: &[f64], g: &mut [f64]| { g[0] = x[0]*x[0] - x[1]; g[1] = x[0] + x[1] - 2.0; }, 2,
+        &[0.0, 0.0], 0.5, 1.0e-8, 0);
+
+    println!("x: {:?} f: {} violation: {} rho: {} evaluations: {}",
+        res.xmin, res.fmin, res.violation, res.rho, res.nr_evaluations);
+
+    assert_float_absolute_eq!(res.xmin[0], 1.0, 1.0e-7);
+    assert_float_absolute_eq!(res.xmin[1], 1.0, 1.0e-7);
+    assert_float_absolute_eq!(res.fmin, 1.0, 1.0e-7);
+    assert!(res.violation < 1.0e-7);
+    assert_eq!(res.rho, 1.0e-8);
+}
+
+#[cfg(test)]
+#[test]
+fn test_unconstrained_and_infeasible_start() {
+    // Without constraints the method is a derivative-free trust-region minimizer.
+    let res = cobyla(
+        |x: &[f64]| (x[0] - 1.0).powi(2) + 10.0 * (x[1] + 2.0).powi(2) + (x[2] - 0.5).powi(2),
+        |_: &[f64], _: &mut [f64]| {}, 0,
+        &[0.0, 0.0, 0.0], 0.5, 1.0e-7, 0);
+
+    println!("x: {:?} f: {} evaluations: {}", res.xmin, res.fmin, res.nr_evaluations);
+    assert_float_absolute_eq!(res.xmin[0], 1.0, 1.0e-5);
+    assert_float_absolute_eq!(res.xmin[1], -2.0, 1.0e-5);
+    assert_float_absolute_eq!(res.xmin[2], 0.5, 1.0e-5);
+
+    // Start far outside the feasible set: minimize x + y subject to x^2 + y^2 <= 1.
+    let res = cobyla(
+        |x: &[f64]| x[0] + x[1],
+        |x: &[f64], g: &mut [f64]| g[0] = x[0]*x[0] + x[1]*x[1] - 1.0, 1,
+        &[3.0, -4.0], 1.0, 1.0e-8, 0);
+
+    println!("x: {:?} f: {} violation: {} evaluations: {}",
+        res.xmin, res.fmin, res.violation, res.nr_evaluations);
+    assert_float_absolute_eq!(res.xmin[0], -(0.5_f64).sqrt(), 1.0e-5);
+    assert_float_absolute_eq!(res.xmin[1], -(0.5_f64).sqrt(), 1.0e-5);
+    assert!(res.violation < 1.0e-7);
+}
+
+#[cfg(test)]
+#[test]
+fn test_max_evaluations() {
+    let res = cobyla(
+        |x: &[f64]| x[0] * x[1],
+        |x: &[f64], g: &mut [f64]| g[0] = x[0]*x[0] + x[1]*x[1] - 1.0, 1,
+        &[1.0, 1.0], 0.5, 1.0e-12, 20);
+
+    assert_eq!(res.nr_evaluations, 20);
+    assert!(res.rho > 1.0e-12);
+}
@@ -0,0 +1,301 @@
+//! Complex-step derivatives of real-analytic functions.
+//!
+//! (c) 2023 Igor Lesik
+//! MIT license
+//!
+//! References:
+//!
+//! 1. William Squire, George Trapp - Using complex variables to estimate derivatives
+//!    of real functions. SIAM Review 40 (1998).
+//! 2. Joaquim R. R. A. Martins, Peter Sturdza, Juan J. Alonso - The complex-step derivative
+//!    approximation. ACM Transactions on Mathematical Software 29 (2003).
+//!
+use std::cmp::Ordering;
+use std::ops::{Add, Sub, Mul, Div, Neg, AddAssign, SubAssign, MulAssign, DivAssign};
+use super::real::Real;
+
+/// Imaginary step, small enough that the `O(h^2)` error is below rounding.
+const STEP: f64 = 1.0e-20_f64;
+
+/// Minimal complex number `re + i im` for complex-step differentiation.
+///
+/// For a real-analytic `f`, `f(x + i h) = f(x) + i h f'(x) + O(h^2)`, so `Im f(x + i h) / h`
+/// is the derivative with no subtraction and no cancellation, accurate to machine
+/// precision for a tiny `h`. Comparisons look at the real part only, and `abs` is
+/// continued analytically as `z` or `-z` by the sign of the real part.
+#[derive(Clone, Copy, Debug)]
+pub struct Complex {
+    /// Real part.
+    pub re: f64,
+    /// Imaginary part.
+    pub im: f64,
+}
+
+impl Complex {
+    /// Complex number with the real part `re` and the imaginary part `im`.
+    pub fn new(re: f64, im: f64) -> Self {
+        Complex { re, im }
+    }
+
+    /// `e^(i theta)`
+    fn cis(theta: f64) -> Self {
+        Complex::new(theta.cos(), theta.sin())
+    }
+
+    fn scale(self, s: f64) -> Self {
+        Complex::new(self.re * s, self.im * s)
+    }
+}
+
+impl PartialEq for Complex {
+    fn eq(&self, other: &Self) -> bool {
+        self.re == other.re
+    }
+}
+
+impl PartialOrd for Complex {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.re.partial_cmp(&other.re)
+    }
+}
+
+impl Add for Complex {
+    type Output = Complex;
+    fn add(self, o: Complex) -> Complex { Complex::new(self.re + o.re, self.im + o.im) }
+}
+
+impl Sub for Complex {
+    type Output = Complex;
+    fn sub(self, o: Complex) -> Complex { Complex::new(self.re - o.re, self.im - o.im) }
+}
+
+impl Mul for Complex {
+    type Output = Complex;
+    fn mul(self, o: Complex) -> Complex {
+        Complex::new(self.re * o.re - self.im * o.im, self.re * o.im + self.im * o.re)
+    }
+}
+
+impl Div for Complex {
+    type Output = Complex;
+    fn div(self, o: Complex) -> Complex {
+        let d = o.re * o.re + o.im * o.im;
+        Complex::new((self.re * o.re + self.im * o.im) / d, (self.im * o.re - self.re * o.im) / d)
+    }
+}
+
+impl Neg for Complex {
+    type Output = Complex;
+    fn neg(self) -> Complex { Complex::new(-self.re, -self.im) }
+}
+
+impl Add<f64> for Complex {
+    type Output = Complex;
+    fn add(self, c: f64) -> Complex { Complex::new(self.re + c, self.im) }
+}
+
+impl Sub<f64> for Complex {
+    type Output = Complex;
+    fn sub(self, c: f64) -> Complex { Complex::new(self.re - c, self.im) }
+}
+
+impl Mul<f64> for Complex {
+    type Output = Complex;
+    fn mul(self, c: f64) -> Complex { self.scale(c) }
+}
+
+impl Div<f64> for Complex {
+    type Output = Complex;
+    fn div(self, c: f64) -> Complex { Complex::new(self.re / c, self.im / c) }
+}
+
+impl Add<Complex> for f64 {
+    type Output = Complex;
+    fn add(self, z: Complex) -> Complex { z + self }
+}
+
+impl Sub<Complex> for f64 {
+    type Output = Complex;
+    fn sub(self, z: Complex) -> Complex { Complex::new(self - z.re, -z.im) }
+}
+
+impl Mul<Complex> for f64 {
+    type Output = Complex;
+    fn mul(self, z: Complex) -> Complex { z.scale(self) }
+}
+
+impl Div<Complex> for f64 {
+    type Output = Complex;
+    fn div(self, z: Complex) -> Complex { Complex::new(self, 0.0) / z }
+}
+
+macro_rules! impl_assign_ops {
+    ($t:ty, $rhs:ty) => {
+        impl AddAssign<$rhs> for $t { fn add_assign(&mut self, o: $rhs) { *self = *self + o; } }
+        impl SubAssign<$rhs> for $t { fn sub_assign(&mut self, o: $rhs) { *self = *self - o; } }
+        impl MulAssign<$rhs> for $t { fn mul_assign(&mut self, o: $rhs) { *self = *self * o; } }
+        impl DivAssign<$rhs> for $t { fn div_assign(&mut self, o: $rhs) { *self = *self / o; } }
+    };
+}
+
+impl_assign_ops!(Complex, Complex);
+impl_assign_ops!(Complex, f64);
+
+impl Real for Complex {
+    fn from_f64(v: f64) -> Self { Complex::new(v, 0.0) }
+    fn value(&self) -> f64 { self.re }
+
+    fn abs(self) -> Self { if self.re < 0.0 { -self } else { self } }
+
+    fn sqrt(self) -> Self {
+        // Principal root, computed without cancellation.
+        let r = self.re.hypot(self.im);
+        if self.re >= 0.0 {
+            let t = ((r + self.re) / 2.0).sqrt();
+            Complex::new(t, if t == 0.0 { 0.0 } else { self.im / (2.0 * t) })
+        }
+        else {
+            let t = ((r - self.re) / 2.0).sqrt();
+            Complex::new(self.im.abs() / (2.0 * t), t.copysign(self.im))
+        }
+    }
+
+    fn exp(self) -> Self { Complex::cis(self.im).scale(self.re.exp()) }
+    fn ln(self) -> Self { Complex::new(self.re.hypot(self.im).ln(), self.im.atan2(self.re)) }
+
+    fn powi(self, n: i32) -> Self {
+        let mut base = if n < 0 { Complex::new(1.0, 0.0) / self } else { self };
+        let mut k = n.unsigned_abs();
+        let mut acc = Complex::new(1.0, 0.0);
+        while k > 0 {
+            if k & 1 == 1 {
+                acc *= base;
+            }
+            base *= base;
+            k >>= 1;
+        }
+        acc
+    }
+
+    fn powf(self, p: f64) -> Self { (self.ln() * p).exp() }
+
+    fn sin(self) -> Self {
+        Complex::new(self.re.sin() * self.im.cosh(), self.re.cos() * self.im.sinh())
+    }
+
+    fn cos(self) -> Self {
+        Complex::new(self.re.cos() * self.im.cosh(), -self.re.sin() * self.im.sinh())
+    }
+
+    fn tan(self) -> Self {
+        let d = (2.0 * self.re).cos() + (2.0 * self.im).cosh();
+        Complex::new((2.0 * self.re).sin() / d, (2.0 * self.im).sinh() / d)
+    }
+
+    fn atan(self) -> Self {
+        let (a, b) = (self.re, self.im);
+        let re = 0.5 * (2.0 * a).atan2(1.0 - a * a - b * b);
+        let im = 0.25 * (4.0 * b / (a * a + (1.0 - b) * (1.0 - b))).ln_1p();
+        Complex::new(re, im)
+    }
+
+    fn sinh(self) -> Self {
+        Complex::new(self.re.sinh() * self.im.cos(), self.re.cosh() * self.im.sin())
+    }
+
+    fn cosh(self) -> Self {
+        Complex::new(self.re.cosh() * self.im.cos(), self.re.sinh() * self.im.sin())
+    }
+
+    fn tanh(self) -> Self {
+        let d = (2.0 * self.re).cosh() + (2.0 * self.im).cos();
+        Complex::new((2.0 * self.re).sinh() / d, (2.0 * self.im).sin() / d)
+    }
+}
+
+/// Turn a real-analytic function of complex numbers into the `(f(x), f'(x))` form that
+/// [`brent_df_search`](crate::brent_df_search) expects, with complex-step derivatives.
+///
+/// - Joaquim R. R. A. Martins et al. - The complex-step derivative approximation (2003).
+///
+/// One complex evaluation at `x + i h`, `h = 1e-20`, gives `f(x)` as its real part and
+/// `f'(x)` as its imaginary part over `h`.
+///
+/// # Example
+///
+/// ```
+/// use rustamath_mnmz::{brent_df_search, with_complex_step, Real};
+/// use assert_float_eq::*;
+/// fn cosine<T: Real>(x: T) -> T { x.cos() }
+///
+/// let (xmin, _, _) = brent_df_search(with_complex_step(cosine), 0.01, 1.0, 0.0, 0);
+///
+/// assert_float_relative_eq!(xmin, std::f64::consts::PI, 1.0e-8);
+/// assert_eq!(with_complex_step(cosine)(1.0).1, -1.0_f64.sin());
+/// ```
+pub fn with_complex_step<F: Fn (Complex) -> Complex>(fun: F) -> impl Fn (f64) -> (f64, f64) {
+    move |x| {
+        let y = fun(Complex::new(x, STEP));
+        (y.re, y.im / STEP)
+    }
+}
+
+/// Value and gradient of `f` at `x` by complex steps, one evaluation per component.
+pub fn complex_step_gradient<F: Fn (&[Complex]) -> Complex>(fun: F, x: &[f64]) -> (f64, Vec<f64>) {
+    let mut z: Vec<Complex> = x.iter().map(|&xi| Complex::new(xi, 0.0)).collect();
+    let mut grad = vec![0.0; x.len()];
+    for i in 0..x.len() {
+        z[i].im = STEP;
+        grad[i] = fun(&z).im / STEP;
+        z[i].im = 0.0;
+    }
+    (fun(&z).re, grad)
+}
+
+#[cfg(test)]
+#[test]
+fn test_machine_precision() {
+    use super::dual::with_dual_derivative;
+    use super::numdiff::{derivative, DiffMethod};
+
+    // Elementary functions against the exact derivatives of forward mode.
+    fn composite<T: Real>(x: T) -> T {
+        let a = (x * x + 1.0).sqrt().ln() * x.sin() - x.cos().exp() / (x.tanh() + 2.0);
+        let b = x.atan() * x.sinh() + x.cosh().powf(0.5) - (x * 0.3).tan() + (-x).abs().powi(-3);
+        a * b
+    }
+    for &x in [-1.3, -0.2, 0.4, 1.1, 2.7].iter() {
+        let (f, df) = with_complex_step(composite)(x);
+        let (_, exact) = with_dual_derivative(composite)(x);
+        println!("x: {} df: {} dual: {} diff: {:e}", x, df, exact, df - exact);
+        assert_float_relative_eq!(f, composite(x), 1.0e-14);
+        assert_float_relative_eq!(df, exact, 1.0e-14);
+    }
+
+    // No cancellation: finite differences lose everything at this scale, complex steps do not.
+    let f = |x: f64| (x * 1.0e-9).exp();
+    let fc = |x: Complex| (x * 1.0e-9).exp();
+    let numeric = derivative(f, 1.0, DiffMethod::Forward, 0.0);
+    let (_, df) = with_complex_step(fc)(1.0);
+    let exact = 1.0e-9 * 1.0e-9_f64.exp();
+    println!("forward: {:e} complex step: {:e}", numeric.df - exact, df - exact);
+    assert_float_relative_eq!(df, exact, 1.0e-15);
+    assert!((numeric.df - exact).abs() > 0.5 * exact);
+}
+
+#[cfg(test)]
+#[test]
+fn test_gradient() {
+    fn rosenbrock<T: Real>(x: &[T]) -> T {
+        (-x[0] + 1.0).powi(2) + (x[1] - x[0] * x[0]).powi(2) * 100.0
+    }
+    let (f, grad) = complex_step_gradient(rosenbrock, &[-1.2, 1.0]);
+    assert_eq!(f, rosenbrock(&[-1.2, 1.0]));
+    assert_float_relative_eq!(grad[0], -215.6, 1.0e-15);
+    assert_float_relative_eq!(grad[1], -88.0, 1.0e-15);
+
+    // Square root on both sides of the branch cut of the principal root.
+    let z = Complex::new(-4.0, 1.0e-3).sqrt();
+    assert_float_relative_eq!((z * z).re, -4.0, 1.0e-15);
+    assert_float_relative_eq!((z * z).im, 1.0e-3, 1.0e-12);
+}
@@ -0,0 +1,272 @@
+//! Augmented Lagrangian method for nonlinear equality and inequality constraints.
+//!
+//! (c) 2023 Igor Lesik
+//! MIT license
+//!
+//! References:
+//!
+//! 1. Ernesto G. Birgin, Jose Mario Martinez - Practical Augmented Lagrangian Methods for
+//!    Constrained Optimization. SIAM (2014).
+//! 2. Jorge Nocedal, Stephen J. Wright - Numerical Optimization. Springer (2006).
+//!    Chapter 17.
+//!
+
+/// Augmented Lagrangian parameters.
+pub struct AugLagOpts {
+    /// Maximum number of outer (multiplier update) iterations.
+    pub max_outer_iterations: usize,
+    /// Tolerance on the constraint violation and on the relative change of the objective.
+    pub tol: f64,
+    /// Initial penalty parameter.
+    pub penalty: f64,
+    /// Factor the penalty is multiplied by when the violation does not decrease enough.
+    pub penalty_factor: f64,
+    /// Upper limit of the penalty parameter.
+    pub max_penalty: f64,
+    /// Required reduction of the violation per outer iteration to keep the penalty.
+    pub violation_decrease: f64,
+}
+
+impl Default for AugLagOpts {
+    fn default() -> Self {
+        AugLagOpts {
+            max_outer_iterations: 50,
+            tol: 1.0e-6,
+            penalty: 10.0,
+            penalty_factor: 10.0,
+            max_penalty: 1.0e12,
+            violation_decrease: 0.5,
+        }
+    }
+}
+
+/// Result of the augmented Lagrangian method.
+pub struct AugLagRes {
+    /// Location of the constrained minimum.
+    pub xmin: Vec<f64>,
+    /// Objective value at `xmin`.
+    pub fmin: f64,
+    /// Largest constraint violation `max(|h_i(x)|, g_j(x))` at `xmin`, 0 if feasible.
+    pub violation: f64,
+    /// Multiplier estimates of the equality constraints.
+    pub lambda_eq: Vec<f64>,
+    /// Multiplier estimates of the inequality constraints, all `>= 0`.
+    pub lambda_ineq: Vec<f64>,
+    /// Final penalty parameter.
+    pub penalty: f64,
+    /// Whether the violation and objective tolerances were met.
+    pub converged: bool,
+    /// Number of outer iterations.
+    pub nr_outer_iterations: usize,
+    /// Sum of iterations reported by the inner minimizer.
+    pub nr_inner_iterations: usize,
+}
+
+/// Augmented Lagrangian method (PHR) for nonlinear constraints.
+///
+/// - Ernesto G. Birgin, Jose Mario Martinez - Practical Augmented Lagrangian Methods for
+///   Constrained Optimization (2014).
+///
+/// Minimizes `fun(x)` subject to `h(x) = 0` and `g(x) <= 0`. The constraint callbacks
+/// `eq(x, h)` and `ineq(x, g)` fill slices of length `m_eq` and `m_ineq`; pass `|_, _| {}`
+/// with a count of 0 when there are no constraints of a kind.
+///
+/// Each outer iteration minimizes the Powell-Hestenes-Rockafellar augmented Lagrangian
+///
+/// ```text
+/// L(x) = f(x) + sum(lambda_i h_i + rho/2 h_i^2) + sum(max(0, mu_j + rho g_j)^2 - mu_j^2) / (2 rho)
+/// ```
+///
+/// with the `local` minimizer, warm started from the previous solution, and then updates the
+/// multipliers `lambda_i += rho h_i`, `mu_j = max(0, mu_j + rho g_j)`. The penalty `rho` grows
+/// by `penalty_factor` whenever the violation fails to shrink by `violation_decrease`.
+///
+/// The local minimizer is called as `local(fun, x)` and must return the same
+/// `(xmin, fmin, nr_iterations)` tuple as [`amoeba`](crate::amoeba).
+///
+/// The multipliers follow the sign convention `grad f + sum lambda_i grad h_i +
+/// sum mu_j grad g_j = 0` at the solution.
+///
+/// # Example
+///
+/// ```
+/// use rustamath_mnmz::{amoeba, augmented_lagrangian, AugLagOpts};
+/// use assert_float_eq::*;
+/// // minimize x^2 + y^2  subject to  x + y >= 1
+/// let res = augmented_lagrangian(
+///     |x: &[f64]| x[0]*x[0] + x[1]*x[1],
+///     |_: &[f64], _: &mut [f64]| {}, 0,
+///     |x: &[f64], g: &mut [f64]| g[0] = 1.0 - x[0] - x[1], 1,
+///     |f, x| amoeba(f, x, 0.1, 1.0e-14, 2000),
+///     &[2.0, 0.0],
+///     &AugLagOpts::default());
+///
+/// assert!(res.converged);
+/// assert_float_absolute_eq!(res.xmin[0], 0.5, 1.0e-5);
+/// assert_float_absolute_eq!(res.xmin[1], 0.5, 1.0e-5);
+/// assert_float_absolute_eq!(res.lambda_ineq[0], 1.0, 1.0e-4);
+/// ```
+#[allow(clippy::too_many_arguments)]
+pub fn augmented_lagrangian<F, E, G, L>(
+    fun: F,
+    eq: E,
+    m_eq: usize,
+    ineq: G,
+    m_ineq: usize,
+    mut local: L,
+    point: &[f64],
+    opts: &AugLagOpts
+) -> AugLagRes
+where
+    F: Fn (&[f64]) -> f64,
+    E: Fn (&[f64], &mut [f64]),
+    G: Fn (&[f64], &mut [f64]),
+    L: FnMut (&dyn Fn (&[f64]) -> f64, &[f64]) -> (Vec<f64>, f64, usize)
+{
+    let mut lambda = vec![0.0; m_eq];
+    let mut mu = vec![0.0; m_ineq];
+    let mut rho = opts.penalty;
+
+    let mut h = vec![0.0; m_eq];
+    let mut g = vec![0.0; m_ineq];
+
+    // Violation including complementarity: an inactive constraint with a positive multiplier
+    // counts as violated by how far the multiplier would push it.
+    let measure = |x: &[f64], mu: &[f64], rho: f64, h: &mut [f64], g: &mut [f64]| {
+        eq(x, h);
+        ineq(x, g);
+        let v_eq = h.iter().fold(0.0_f64, |v, hi| v.max(hi.abs()));
+        let v_ineq = g.iter().zip(mu.iter()).fold(0.0_f64, |v, (gj, mj)| v.max(gj.max(-mj / rho).abs()));
+        v_eq.max(v_ineq)
+    };
+
+    let mut x = point.to_vec();
+    let mut fx = fun(&x);
+    let mut violation_prev = measure(&x, &mu, rho, &mut h, &mut g);
+    let mut converged = false;
+    let mut nr_inner_iterations: usize = 0;
+    let mut nr_outer_iterations: usize = 0;
+
+    while nr_outer_iterations < opts.max_outer_iterations {
+        nr_outer_iterations += 1;
+
+        let augmented = |x: &[f64]| {
+            let mut h = vec![0.0; m_eq];
+            let mut g = vec![0.0; m_ineq];
+            eq(x, &mut h);
+            ineq(x, &mut g);
+            let mut l = fun(x);
+            for (hi, li) in h.iter().zip(lambda.iter()) {
+                l += li * hi + 0.5 * rho * hi * hi;
+            }
+            for (gj, mj) in g.iter().zip(mu.iter()) {
+                let s = (mj + rho * gj).max(0.0);
+                l += (s * s - mj * mj) / (2.0 * rho);
+            }
+            l
+        };
+
+        let (xnew, _, nr_inner) = local(&augmented, &x);
+        nr_inner_iterations += nr_inner;
+        let fnew = fun(&xnew);
+
+        let violation = measure(&xnew, &mu, rho, &mut h, &mut g);
+
+        for (li, hi) in lambda.iter_mut().zip(h.iter()) {
+            *li += rho * hi;
+        }
+        for (mj, gj) in mu.iter_mut().zip(g.iter()) {
+            *mj = (*mj + rho * gj).max(0.0);
+        }
+
+        let df = (fnew - fx).abs();
+        x = xnew;
+        fx = fnew;
+
+        if violation <= opts.tol && df <= opts.tol * (1.0 + fx.abs()) {
+            converged = true;
+            break;
+        }
+
+        if violation > opts.tol && violation > opts.violation_decrease * violation_prev {
+            rho = (rho * opts.penalty_factor).min(opts.max_penalty);
+        }
+        violation_prev = violation;
+    }
+
+    eq(&x, &mut h);
+    ineq(&x, &mut g);
+    let violation = h.iter().map(|hi| hi.abs()).chain(g.iter().copied()).fold(0.0_f64, f64::max);
+
+    AugLagRes {
+        xmin: x,
+        fmin: fx,
+        violation,
+        lambda_eq: lambda,
+        lambda_ineq: mu,
+        penalty: rho,
+        converged,
+        nr_outer_iterations,
+        nr_inner_iterations,
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_equality() {
+    use super::amoeba;
+
+    // minimize (x - 1)^2 + (y - 2)^2  subject to  x + y = 1, solution (0, 1) with lambda = 2
+    let res = augmented_lagrangian(
+        |x: &[f64]| (x[0] - 1.0).powi(2) + (x[1] - 2.0).powi(2),
+        |x: &[f64], h: &mut [f64]| h[0] = x[0] + x[1] - 1.0, 1,
+        |_: &[f64], _: &mut [f64]| {}, 0,
+        |f, x| amoeba(f, x, 0.1, 1.0e-14, 2000),
+        &[3.0, 3.0],
+        &AugLagOpts::default());
+
+    println!("x: {:?} f: {} violation: {} lambda: {:?} rho: {} outer: {} inner: {}",
+        res.xmin, res.fmin, res.violation, res.lambda_eq, res.penalty,
+        res.nr_outer_iterations, res.nr_inner_iterations);
+
+    assert!(res.converged);
+    assert_float_absolute_eq!(res.xmin[0], 0.0, 1.0e-5);
+    assert_float_absolute_eq!(res.xmin[1], 1.0, 1.0e-5);
+    assert_float_absolute_eq!(res.lambda_eq[0], 2.0, 1.0e-3);
+    assert!(res.violation < 1.0e-6);
+}
+
+#[cfg(test)]
+#[test]
+fn test_rosenbrock_disk() {
+    use super::amoeba;
+
+    // Rosenbrock inside the disk x^2 + y^2 <= 1.5 and in the half plane x >= 0;
+    // the unconstrained minimum (1, 1) is outside the disk.
+    let rosenbrock = |x: &[f64]| (1.0 - x[0]).powi(2) + 100.0 * (x[1] - x[0]*x[0]).powi(2);
+
+    let res = augmented_lagrangian(
+        rosenbrock,
+        |_: &[f64], _: &mut [f64]| {}, 0,
+        |x: &[f64], g: &mut [f64]| { g[0] = x[0]*x[0] + x[1]*x[1] - 1.5; g[1] = -x[0]; }, 2,
+        |f, x| amoeba(f, x, 0.1, 1.0e-15, 5000),
+        &[-0.5, 0.5],
+        &AugLagOpts::default());
+
+    println!("x: {:?} f: {} violation: {} mu: {:?} rho: {} outer: {} inner: {}",
+        res.xmin, res.fmin, res.violation, res.lambda_ineq, res.penalty,
+        res.nr_outer_iterations, res.nr_inner_iterations);
+
+    assert!(res.converged);
+    assert_float_absolute_eq!(res.xmin[0]*res.xmin[0] + res.xmin[1]*res.xmin[1], 1.5, 1.0e-6);
+    assert!(res.lambda_ineq[0] > 0.0);
+    assert_eq!(res.lambda_ineq[1], 0.0);
+
+    // Stationarity of the Lagrangian.
+    let (x, y) = (res.xmin[0], res.xmin[1]);
+    let mu = res.lambda_ineq[0];
+    let gx = -2.0 * (1.0 - x) - 400.0 * x * (y - x*x) + 2.0 * mu * x;
+    let gy = 200.0 * (y - x*x) + 2.0 * mu * y;
+    assert_float_absolute_eq!(gx, 0.0, 1.0e-3);
+    assert_float_absolute_eq!(gy, 0.0, 1.0e-3);
+}
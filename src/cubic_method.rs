@@ -0,0 +1,215 @@
+//! Cubic interpolation with First Derivative for searching for a minimum.
+//!
+//! (c) 2023 Igor Lesik
+//! MIT license
+//!
+//! References:
+//!
+//! 1. William C. Davidon - Variable metric method for minimization.
+//!    Argonne National Laboratory report ANL-5990 (1959).
+//! 2. Jorge Nocedal, Stephen J. Wright - Numerical Optimization.
+//!    Springer (2006). Section 3.5.
+//! 3. William H. Press - Numerical recipes, the art of scientific computing.
+//!    Cambridge University Press (2007).
+//!
+use super::bracket::find_bracket;
+
+/// Smallest tolerance.
+///
+/// See book "Numerical recipes, the art of scientific computing."
+/// sqrt(f64 precision 10^16), by Tailor series for `f(x+eps)`
+const MIN_TOLERANCE: f64 = 3.0e-8_f64;
+
+/// Davidon's cubic interpolation to search for a minimum.
+///
+/// - William C. Davidon - Variable metric method for minimization (1959).
+/// - Jorge Nocedal, Stephen J. Wright - Numerical Optimization (2006), equation 3.59.
+///
+/// The function `fun(x)` returns `(f(x), f'(x))`, as for
+/// [`brent_df_search`](crate::brent_df_search). A minimum is first bracketed by
+/// [`find_bracket`](crate::find_bracket). Every iteration fits the cubic Hermite
+/// interpolant to the values and derivatives at the best point `x` and the previous point
+/// `w`, and steps to its minimizer
+/// `u = x - (x - w) (f'(x) + d2 - d1) / (f'(x) - f'(w) + 2 d2)` with
+/// `d1 = f'(w) + f'(x) - 3 (f(w) - f(x)) / (w - x)`, `d2 = sign(x - w) sqrt(d1^2 - f'(w) f'(x))`.
+/// The cubic is exact for quadratic and cubic functions and converges superlinearly.
+///
+/// The safeguards are those of `brent_df_search`: the step must stay inside the bracket,
+/// go in the downhill direction of `f'(x)` and be less than half the step before last;
+/// otherwise, or when the cubic has no minimum, the bracket is bisected on the downhill
+/// side.
+///
+/// Returns the location of the minimum, the function value there and the number of
+/// iterations.
+///
+/// # Example
+///
+/// ```
+/// use rustamath_mnmz::{cubic_search, brent_df_search};
+/// use assert_float_eq::*;
+/// let cosine = |x: f64| (x.cos(), -(x.sin())); // Minimum at Pi when x ∈ [0, 2*Pi].
+/// let range = (0.01, 1.0);
+///
+/// let (xmin, f, nr_iterations) = cubic_search(cosine, range.0, range.1, 0.0, 0);
+/// let (_, _, nr_iterations_df) = brent_df_search(cosine, range.0, range.1, 0.0, 0);
+///
+/// println!("xmin: {:.8} f(xmin): {:6.2} iterations: {} vs brent_df {}",
+///     xmin, f, nr_iterations, nr_iterations_df);
+///
+/// assert_float_relative_eq!(xmin, std::f64::consts::PI, 1.0e-8);
+/// ```
+pub fn cubic_search<F: Fn (f64) -> (f64, f64)>(
+    fun: F,
+    a: f64,
+    b: f64,
+    tol: f64,
+    max_iterations: usize
+) -> (f64, f64, usize)
+{
+    let tol = tol.max(MIN_TOLERANCE);
+    let max_iterations = if max_iterations < 1 { 500 } else { max_iterations.min(1000) };
+
+    // Protects against trying to achieve fractional accuracy for a minimum at zero.
+    const ZEPS: f64 = f64::EPSILON * 1.0e-3;
+
+    let bracket = find_bracket(|x| fun(x).0, a, b);
+
+    // a and b must be in ascending order, but input abscissas need not be.
+    let mut a = bracket.a.min(bracket.c);
+    let mut b = bracket.a.max(bracket.c);
+
+    // Best point inside the bracket, and the lower of the bracket ends as the second point.
+    let mut x = bracket.b;
+    let mut w = if bracket.fa < bracket.fc { bracket.a } else { bracket.c };
+    let (mut fx, mut dx) = fun(x);
+    let (mut fw, mut dw) = fun(w);
+
+    // Movement on the last step and on the step before last.
+    let mut d: f64 = 0.0;
+    let mut e: f64 = 0.0;
+
+    let mut nr_iterations: usize = 0;
+
+    for _i in 0..max_iterations {
+        let xm = 0.5 * (a + b);
+        let tol1 = tol * x.abs() + ZEPS;
+        let tol2 = 2.0 * (tol1 + ZEPS);
+
+        if (x - xm).abs() <= (tol2 - 0.5 * (b - a)) {
+            break;
+        }
+
+        let olde = e;
+        let mut cubic = None;
+        if w != x {
+            let d1 = dw + dx - 3.0 * (fw - fx) / (w - x);
+            let disc = d1 * d1 - dw * dx;
+            if disc >= 0.0 {
+                let d2 = disc.sqrt().copysign(x - w);
+                let denom = dx - dw + 2.0 * d2;
+                if denom != 0.0 {
+                    cubic = Some(-(x - w) * (dx + d2 - d1) / denom);
+                }
+            }
+        }
+
+        // Take the cubic step only if it is in the bracket, downhill, and shrinking.
+        match cubic {
+            Some(step) if (a - (x + step)) * ((x + step) - b) > 0.0 && dx * step <= 0.0
+                && (olde == 0.0 || step.abs() <= (0.5 * olde).abs()) =>
+            {
+                e = d;
+                d = step;
+                let u = x + d;
+                if u - a < tol2 || b - u < tol2 {
+                    d = tol1.copysign(xm - x);
+                }
+            }
+            _ => {
+                // Bisect the downhill segment.
+                e = if dx >= 0.0 { a - x } else { b - x };
+                d = 0.5 * e;
+            }
+        }
+
+        let u: f64;
+        let fu: f64;
+        let du: f64;
+
+        if d.abs() >= tol1 {
+            u = x + d;
+            (fu, du) = fun(u);
+        }
+        else {
+            u = x + tol1.copysign(d);
+            (fu, du) = fun(u);
+            // If the minimum step in the downhill direction takes us uphill,
+            // then we are done.
+            if fu > fx {
+                break;
+            }
+        }
+
+        if fu <= fx {
+            if u >= x { a = x; } else { b = x; }
+            (w, fw, dw) = (x, fx, dx);
+            (x, fx, dx) = (u, fu, du);
+        }
+        else {
+            if u < x { a = u; } else { b = u; }
+            (w, fw, dw) = (u, fu, du);
+        }
+
+        nr_iterations += 1;
+    }
+
+    (x, fx, nr_iterations)
+}
+
+#[cfg(test)]
+#[test]
+fn test_cosine() {
+    use super::brent_df_search;
+
+    // Minimum at Pi when x ∈ [0, 2*Pi].
+    let cosine = |x: f64| (x.cos(), -(x.sin()));
+
+    let ranges = vec![(0.01, 1.0), (2.0, 2.5), (5.0, 4.0)];
+
+    for range in ranges {
+        let (xmin, f, nr_iterations) = cubic_search(cosine, range.0, range.1, 0.0, 0);
+        let (xmin_df, _, nr_iterations_df) = brent_df_search(cosine, range.0, range.1, 0.0, 0);
+
+        println!("xmin: {:.8} f(xmin): {:6.2} iterations: {} vs brent_df {}",
+            xmin, f, nr_iterations, nr_iterations_df
+        );
+
+        assert_float_relative_eq!(xmin, std::f64::consts::PI, 1.0e-8);
+        assert_float_relative_eq!(xmin_df, std::f64::consts::PI, 1.0e-8);
+        assert!(nr_iterations <= nr_iterations_df);
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_poly2() {
+    use super::brent_df_search;
+
+    // Roots 1.0 and 2.0, minimum at 1.5.
+    let poly2 = |x: f64| ((x-1.0)*(x-2.0), 2.0*x-3.0);
+
+    let ranges = vec![(10.0, 20.0), (20.0, 10.0), (-10.0, 0.0),
+        (-2000.0, -1000.0), (-10_000.0, 30_000.0), (0.0001, 0.0002), (-0.00001, 1.4999)];
+
+    for range in ranges {
+        let (xmin, f, nr_iterations) = cubic_search(poly2, range.0, range.1, 0.0, 0);
+        let (_, _, nr_iterations_df) = brent_df_search(poly2, range.0, range.1, 0.0, 0);
+
+        println!("xmin: {:.8} f(xmin): {:6.2} iterations: {} vs brent_df {}",
+            xmin, f, nr_iterations, nr_iterations_df
+        );
+
+        assert_float_relative_eq!(xmin, 1.5, 1.0e-8);
+        assert!(nr_iterations <= nr_iterations_df);
+    }
+}
@@ -0,0 +1,299 @@
+//! DIRECT global minimization over a hyper-rectangle.
+//!
+//! (c) 2023 Igor Lesik
+//! MIT license
+//!
+//! References:
+//!
+//! 1. D. R. Jones, C. D. Perttunen, B. E. Stuckman - Lipschitzian optimization without
+//!    the Lipschitz constant. Journal of Optimization Theory and Applications 79 (1993).
+//! 2. J. M. Gablonsky, C. T. Kelley - A locally-biased form of the DIRECT algorithm.
+//!    Journal of Global Optimization 21 (2001).
+//!
+
+/// Minimal improvement over the best value a rectangle must promise to be divided.
+const EPSILON: f64 = 1.0e-4_f64;
+
+/// Variant of the DIRECT algorithm.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DirectVariant {
+    /// Original DIRECT of Jones et al., rectangles measured by their half-diagonal.
+    Original,
+    /// DIRECT-L of Gablonsky and Kelley, rectangles measured by their longest side and
+    /// only one rectangle of each size divided per iteration.
+    LocallyBiased,
+}
+
+/// Result of the DIRECT search.
+pub struct DirectRes {
+    /// Location of the minimum.
+    pub xmin: Vec<f64>,
+    /// f(xmin)
+    pub fmin: f64,
+    /// Number of rectangles the box was divided into.
+    pub nr_rectangles: usize,
+    /// Number of function evaluations.
+    pub nr_evaluations: usize,
+    /// Number of iterations.
+    pub nr_iterations: usize,
+}
+
+struct Rect {
+    // Center in the unit hypercube.
+    center: Vec<f64>,
+    f: f64,
+    // Side along dimension i is `3^-levels[i]`.
+    levels: Vec<i32>,
+    size: f64,
+}
+
+impl Rect {
+    fn new(center: Vec<f64>, f: f64, levels: Vec<i32>, variant: DirectVariant) -> Self {
+        let size = match variant {
+            DirectVariant::Original => {
+                // Sum in a fixed order so that equal rectangles get bitwise equal sizes.
+                let mut sorted = levels.clone();
+                sorted.sort_unstable();
+                0.5 * sorted.iter().map(|&k| 9.0_f64.powi(-k)).sum::<f64>().sqrt()
+            }
+            DirectVariant::LocallyBiased => {
+                0.5 * 3.0_f64.powi(-levels.iter().copied().min().unwrap_or(0))
+            }
+        };
+        Rect { center, f, levels, size }
+    }
+}
+
+/// DIRECT (DIviding RECTangles) global minimization.
+///
+/// - D. R. Jones, C. D. Perttunen, B. E. Stuckman - Lipschitzian optimization without
+///   the Lipschitz constant (1993).
+/// - J. M. Gablonsky, C. T. Kelley - A locally-biased form of the DIRECT algorithm (2001).
+///
+/// Minimizes `fun` over the box `lower[i] <= x[i] <= upper[i]`. The box is repeatedly
+/// trisected; in every iteration the rectangles that could contain the minimum for some
+/// Lipschitz constant (the "potentially optimal" ones) are divided further. The method is
+/// deterministic and needs no derivatives or tuning parameters.
+///
+/// The search stops once `max_evaluations` function evaluations were used
+/// (1000 if 0) or after `max_iterations` iterations (100 if 0).
+///
+/// # Example
+///
+/// ```
+/// use rustamath_mnmz::{direct, DirectVariant};
+/// use assert_float_eq::*;
+/// // Six-hump camel function, global minimum -1.0316 at (0.0898, -0.7126) and (-0.0898, 0.7126).
+/// let camel = |x: &[f64]| {
+///     let (x, y) = (x[0], x[1]);
+///     (4.0 - 2.1*x*x + x*x*x*x/3.0)*x*x + x*y + (-4.0 + 4.0*y*y)*y*y
+/// };
+///
+/// let res = direct(camel, &[-3.0, -2.0], &[3.0, 2.0], DirectVariant::Original, 1000, 0);
+///
+/// println!("min: {}, {} fmin: {} rectangles: {}", res.xmin[0], res.xmin[1], res.fmin, res.nr_rectangles);
+///
+/// assert_float_absolute_eq!(res.fmin, -1.0316, 1.0e-3);
+/// ```
+pub fn direct<F: Fn (&[f64]) -> f64>(
+    fun: F,
+    lower: &[f64],
+    upper: &[f64],
+    variant: DirectVariant,
+    max_evaluations: usize,
+    max_iterations: usize
+) -> DirectRes
+{
+    let max_evaluations = if max_evaluations < 1 { 1000 } else { max_evaluations };
+    let max_iterations = if max_iterations < 1 { 100 } else { max_iterations };
+
+    let ndim = lower.len();
+    let mut x = vec![0.0; ndim];
+
+    let eval = |c: &[f64], x: &mut [f64]| {
+        for i in 0..ndim {
+            x[i] = lower[i] + c[i] * (upper[i] - lower[i]);
+        }
+        fun(x)
+    };
+
+    let center = vec![0.5; ndim];
+    let f = eval(&center, &mut x);
+    let mut rects = vec![Rect::new(center, f, vec![0; ndim], variant)];
+    let mut nr_evaluations: usize = 1;
+    let mut imin = 0;
+    let mut nr_iterations: usize = 0;
+
+    while nr_evaluations < max_evaluations && nr_iterations < max_iterations {
+        let selected = potentially_optimal(&rects, imin, variant);
+
+        for r in selected {
+            if nr_evaluations >= max_evaluations {
+                break;
+            }
+
+            // Divide along the longest sides only.
+            let kmin = rects[r].levels.iter().copied().min().unwrap_or(0);
+            let delta = 3.0_f64.powi(-(kmin + 1));
+            let long_dims: Vec<usize> = (0..ndim).filter(|&i| rects[r].levels[i] == kmin).collect();
+
+            // Sample both neighbors along every long side.
+            let mut samples = Vec::with_capacity(long_dims.len());
+            for &i in &long_dims {
+                let mut c_minus = rects[r].center.clone();
+                let mut c_plus = rects[r].center.clone();
+                c_minus[i] -= delta;
+                c_plus[i] += delta;
+                let f_minus = eval(&c_minus, &mut x);
+                let f_plus = eval(&c_plus, &mut x);
+                nr_evaluations += 2;
+                samples.push((f_minus.min(f_plus), i, c_minus, f_minus, c_plus, f_plus));
+            }
+
+            // The best sampled dimension is split first so that its children get the
+            // largest rectangles.
+            samples.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+            let mut levels = rects[r].levels.clone();
+            for (_, i, c_minus, f_minus, c_plus, f_plus) in samples {
+                levels[i] += 1;
+                rects.push(Rect::new(c_minus, f_minus, levels.clone(), variant));
+                rects.push(Rect::new(c_plus, f_plus, levels.clone(), variant));
+            }
+            let center = std::mem::take(&mut rects[r].center);
+            rects[r] = Rect::new(center, rects[r].f, levels, variant);
+        }
+
+        for (i, rect) in rects.iter().enumerate() {
+            if rect.f < rects[imin].f {
+                imin = i;
+            }
+        }
+
+        nr_iterations += 1;
+    }
+
+    let xmin = (0..ndim).map(|i| lower[i] + rects[imin].center[i] * (upper[i] - lower[i])).collect();
+
+    DirectRes {
+        xmin,
+        fmin: rects[imin].f,
+        nr_rectangles: rects.len(),
+        nr_evaluations,
+        nr_iterations,
+    }
+}
+
+// Indices of the potentially optimal rectangles: the lower-right convex hull of the
+// (size, f) cloud, starting at the best rectangle, minus those that can't improve the
+// best value by more than `EPSILON`.
+fn potentially_optimal(rects: &[Rect], imin: usize, variant: DirectVariant) -> Vec<usize> {
+    let fmin = rects[imin].f;
+
+    // Lowest rectangle of every size, smaller rectangles than the best one can't be optimal.
+    let mut order: Vec<usize> = (0..rects.len()).filter(|&i| rects[i].size >= rects[imin].size).collect();
+    order.sort_by(|&a, &b| rects[a].size.total_cmp(&rects[b].size).then(rects[a].f.total_cmp(&rects[b].f)));
+
+    let mut groups: Vec<usize> = Vec::new(); // first (lowest) rectangle in each size group
+    for &i in &order {
+        if groups.last().is_none_or(|&g| rects[g].size != rects[i].size) {
+            groups.push(i);
+        }
+    }
+
+    // Skip size groups that are as big as the best rectangle but not as good.
+    let start = groups.iter().rposition(|&g| rects[g].f <= fmin).unwrap_or(0);
+    let groups = &groups[start..];
+
+    // Lower convex hull, collinear points are kept.
+    let mut hull: Vec<usize> = Vec::new();
+    for &g in groups {
+        while hull.len() >= 2 {
+            let o = &rects[hull[hull.len()-2]];
+            let a = &rects[hull[hull.len()-1]];
+            let b = &rects[g];
+            let cross = (a.size - o.size) * (b.f - o.f) - (a.f - o.f) * (b.size - o.size);
+            if cross < 0.0 { hull.pop(); } else { break; }
+        }
+        hull.push(g);
+    }
+
+    let mut selected = Vec::new();
+    for (j, &h) in hull.iter().enumerate() {
+        if j + 1 < hull.len() {
+            // The largest Lipschitz constant for which `h` is still on the hull.
+            let next = &rects[hull[j+1]];
+            let k = (next.f - rects[h].f) / (next.size - rects[h].size);
+            if rects[h].f - k * rects[h].size > fmin - EPSILON * fmin.abs() {
+                continue;
+            }
+        }
+        match variant {
+            DirectVariant::Original => {
+                selected.extend(order.iter().copied()
+                    .filter(|&i| rects[i].size == rects[h].size && rects[i].f == rects[h].f));
+            }
+            DirectVariant::LocallyBiased => selected.push(h),
+        }
+    }
+
+    selected
+}
+
+#[cfg(test)]
+#[test]
+fn test_branin() {
+    // Global minimum 0.397887 at (-Pi, 12.275), (Pi, 2.275) and (9.42478, 2.475).
+    let branin = |x: &[f64]| {
+        let pi = std::f64::consts::PI;
+        let (a, b, c) = (1.0, 5.1/(4.0*pi*pi), 5.0/pi);
+        let (r, s, t) = (6.0, 10.0, 1.0/(8.0*pi));
+        a*(x[1] - b*x[0]*x[0] + c*x[0] - r).powi(2) + s*(1.0 - t)*x[0].cos() + s
+    };
+
+    for variant in [DirectVariant::Original, DirectVariant::LocallyBiased] {
+        let res = direct(branin, &[-5.0, 0.0], &[10.0, 15.0], variant, 2000, 0);
+
+        println!("{:?} min: {}, {} fmin: {} rectangles: {} evaluations: {} iterations: {}",
+            variant, res.xmin[0], res.xmin[1], res.fmin, res.nr_rectangles,
+            res.nr_evaluations, res.nr_iterations);
+
+        assert_float_absolute_eq!(res.fmin, 0.397887, 1.0e-4);
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_camel() {
+    let camel = |x: &[f64]| {
+        let (x, y) = (x[0], x[1]);
+        (4.0 - 2.1*x*x + x*x*x*x/3.0)*x*x + x*y + (-4.0 + 4.0*y*y)*y*y
+    };
+
+    for variant in [DirectVariant::Original, DirectVariant::LocallyBiased] {
+        let res = direct(camel, &[-3.0, -2.0], &[3.0, 2.0], variant, 2000, 0);
+
+        println!("{:?} min: {}, {} fmin: {} rectangles: {} evaluations: {}",
+            variant, res.xmin[0], res.xmin[1], res.fmin, res.nr_rectangles, res.nr_evaluations);
+
+        assert_float_absolute_eq!(res.fmin, -1.031628, 1.0e-4);
+        assert_float_absolute_eq!(res.xmin[0].abs(), 0.0898, 1.0e-2);
+        assert_float_absolute_eq!(res.xmin[1].abs(), 0.7126, 1.0e-2);
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_budget() {
+    let sphere = |x: &[f64]| x.iter().map(|xi| (xi - 0.3)*(xi - 0.3)).sum::<f64>();
+
+    let res = direct(sphere, &[-1.0; 4], &[1.0; 4], DirectVariant::LocallyBiased, 200, 0);
+
+    println!("min: {:?} fmin: {} rectangles: {} evaluations: {}",
+        res.xmin, res.fmin, res.nr_rectangles, res.nr_evaluations);
+
+    // Only the last division may overshoot the budget, by at most 2 points per dimension.
+    assert!(res.nr_evaluations <= 200 + 2*4);
+    assert_eq!(res.nr_rectangles, res.nr_evaluations);
+    assert!(res.fmin < 0.01);
+}
@@ -0,0 +1,253 @@
+//! Forward-mode automatic differentiation with dual numbers.
+//!
+//! (c) 2023 Igor Lesik
+//! MIT license
+//!
+//! References:
+//!
+//! 1. Andreas Griewank, Andrea Walther - Evaluating Derivatives: Principles and Techniques
+//!    of Algorithmic Differentiation. SIAM (2008).
+//!
+use std::ops::{Add, Sub, Mul, Div, Neg, AddAssign, SubAssign, MulAssign, DivAssign};
+use super::real::Real;
+
+/// Dual number `re + eps e` with `e^2 = 0`.
+///
+/// Evaluating `f(x + e)` gives `f(x) + f'(x) e`, the exact derivative in one pass.
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+pub struct Dual {
+    /// Value.
+    pub re: f64,
+    /// Derivative.
+    pub eps: f64,
+}
+
+impl Dual {
+    /// Dual number with the value `re` and the derivative `eps`.
+    pub fn new(re: f64, eps: f64) -> Self {
+        Dual { re, eps }
+    }
+
+    /// Independent variable `x` with derivative 1.
+    pub fn variable(x: f64) -> Self {
+        Dual { re: x, eps: 1.0 }
+    }
+
+    /// Constant `x` with derivative 0.
+    pub fn constant(x: f64) -> Self {
+        Dual { re: x, eps: 0.0 }
+    }
+
+    // f(u) with f' = df at u.
+    #[inline] fn chain(self, f: f64, df: f64) -> Self {
+        Dual { re: f, eps: df * self.eps }
+    }
+}
+
+impl Add for Dual {
+    type Output = Dual;
+    fn add(self, o: Dual) -> Dual { Dual::new(self.re + o.re, self.eps + o.eps) }
+}
+
+impl Sub for Dual {
+    type Output = Dual;
+    fn sub(self, o: Dual) -> Dual { Dual::new(self.re - o.re, self.eps - o.eps) }
+}
+
+impl Mul for Dual {
+    type Output = Dual;
+    fn mul(self, o: Dual) -> Dual { Dual::new(self.re * o.re, self.eps * o.re + self.re * o.eps) }
+}
+
+impl Div for Dual {
+    type Output = Dual;
+    fn div(self, o: Dual) -> Dual {
+        Dual::new(self.re / o.re, (self.eps * o.re - self.re * o.eps) / (o.re * o.re))
+    }
+}
+
+impl Neg for Dual {
+    type Output = Dual;
+    fn neg(self) -> Dual { Dual::new(-self.re, -self.eps) }
+}
+
+impl Add<f64> for Dual {
+    type Output = Dual;
+    fn add(self, c: f64) -> Dual { Dual::new(self.re + c, self.eps) }
+}
+
+impl Sub<f64> for Dual {
+    type Output = Dual;
+    fn sub(self, c: f64) -> Dual { Dual::new(self.re - c, self.eps) }
+}
+
+impl Mul<f64> for Dual {
+    type Output = Dual;
+    fn mul(self, c: f64) -> Dual { Dual::new(self.re * c, self.eps * c) }
+}
+
+impl Div<f64> for Dual {
+    type Output = Dual;
+    fn div(self, c: f64) -> Dual { Dual::new(self.re / c, self.eps / c) }
+}
+
+impl Add<Dual> for f64 {
+    type Output = Dual;
+    fn add(self, d: Dual) -> Dual { d + self }
+}
+
+impl Sub<Dual> for f64 {
+    type Output = Dual;
+    fn sub(self, d: Dual) -> Dual { Dual::new(self - d.re, -d.eps) }
+}
+
+impl Mul<Dual> for f64 {
+    type Output = Dual;
+    fn mul(self, d: Dual) -> Dual { d * self }
+}
+
+impl Div<Dual> for f64 {
+    type Output = Dual;
+    fn div(self, d: Dual) -> Dual { Dual::constant(self) / d }
+}
+
+macro_rules! impl_assign_ops {
+    ($t:ty, $rhs:ty) => {
+        impl AddAssign<$rhs> for $t { fn add_assign(&mut self, o: $rhs) { *self = *self + o; } }
+        impl SubAssign<$rhs> for $t { fn sub_assign(&mut self, o: $rhs) { *self = *self - o; } }
+        impl MulAssign<$rhs> for $t { fn mul_assign(&mut self, o: $rhs) { *self = *self * o; } }
+        impl DivAssign<$rhs> for $t { fn div_assign(&mut self, o: $rhs) { *self = *self / o; } }
+    };
+}
+
+impl_assign_ops!(Dual, Dual);
+impl_assign_ops!(Dual, f64);
+
+impl Real for Dual {
+    fn from_f64(v: f64) -> Self { Dual::constant(v) }
+    fn value(&self) -> f64 { self.re }
+
+    fn abs(self) -> Self { self.chain(self.re.abs(), if self.re < 0.0 { -1.0 } else { 1.0 }) }
+    fn sqrt(self) -> Self { let s = self.re.sqrt(); self.chain(s, 0.5 / s) }
+    fn exp(self) -> Self { let e = self.re.exp(); self.chain(e, e) }
+    fn ln(self) -> Self { self.chain(self.re.ln(), 1.0 / self.re) }
+    fn powi(self, n: i32) -> Self { self.chain(self.re.powi(n), n as f64 * self.re.powi(n - 1)) }
+    fn powf(self, p: f64) -> Self { self.chain(self.re.powf(p), p * self.re.powf(p - 1.0)) }
+    fn sin(self) -> Self { self.chain(self.re.sin(), self.re.cos()) }
+    fn cos(self) -> Self { self.chain(self.re.cos(), -self.re.sin()) }
+    fn tan(self) -> Self { let t = self.re.tan(); self.chain(t, 1.0 + t * t) }
+    fn atan(self) -> Self { self.chain(self.re.atan(), 1.0 / (1.0 + self.re * self.re)) }
+    fn sinh(self) -> Self { self.chain(self.re.sinh(), self.re.cosh()) }
+    fn cosh(self) -> Self { self.chain(self.re.cosh(), self.re.sinh()) }
+    fn tanh(self) -> Self { let t = self.re.tanh(); self.chain(t, 1.0 - t * t) }
+}
+
+/// Turn a function of dual numbers into the exact `(f(x), f'(x))` form that
+/// [`brent_df_search`](crate::brent_df_search) expects.
+///
+/// # Example
+///
+/// ```
+/// use rustamath_mnmz::{brent_df_search, with_dual_derivative, Real};
+/// use assert_float_eq::*;
+/// fn cosine<T: Real>(x: T) -> T { x.cos() }
+///
+/// let (xmin, _, _) = brent_df_search(with_dual_derivative(cosine), 0.01, 1.0, 0.0, 0);
+///
+/// assert_float_relative_eq!(xmin, std::f64::consts::PI, 1.0e-8);
+/// ```
+pub fn with_dual_derivative<F: Fn (Dual) -> Dual>(fun: F) -> impl Fn (f64) -> (f64, f64) {
+    move |x| {
+        let y = fun(Dual::variable(x));
+        (y.re, y.eps)
+    }
+}
+
+/// Value and gradient of `f` at `x` by forward mode, one pass per component.
+///
+/// # Example
+///
+/// ```
+/// use rustamath_mnmz::{dual_gradient, Real};
+/// fn rosenbrock<T: Real>(x: &[T]) -> T {
+///     (-x[0] + 1.0).powi(2) + (x[1] - x[0] * x[0]).powi(2) * 100.0
+/// }
+///
+/// let (f, grad) = dual_gradient(rosenbrock, &[-1.2, 1.0]);
+///
+/// assert_eq!(f, rosenbrock(&[-1.2, 1.0]));
+/// assert!((grad[0] + 215.6).abs() < 1.0e-12 && (grad[1] + 88.0).abs() < 1.0e-12);
+/// ```
+pub fn dual_gradient<F: Fn (&[Dual]) -> Dual>(fun: F, x: &[f64]) -> (f64, Vec<f64>) {
+    let mut xd: Vec<Dual> = x.iter().map(|&xi| Dual::constant(xi)).collect();
+    let mut grad = vec![0.0; x.len()];
+    let mut f = 0.0;
+    for i in 0..x.len() {
+        xd[i].eps = 1.0;
+        let y = fun(&xd);
+        xd[i].eps = 0.0;
+        f = y.re;
+        grad[i] = y.eps;
+    }
+    if x.is_empty() {
+        f = fun(&xd).re;
+    }
+    (f, grad)
+}
+
+/// Turn a function of dual numbers into the `fun(x, grad) -> f(x)` form of the gradient
+/// methods, such as [`lbfgsb`](crate::lbfgsb).
+pub fn with_dual_gradient<F: Fn (&[Dual]) -> Dual>(fun: F) -> impl Fn (&[f64], &mut [f64]) -> f64 {
+    move |x, g| {
+        let (f, grad) = dual_gradient(&fun, x);
+        g.copy_from_slice(&grad);
+        f
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_elementary_functions() {
+    use super::numdiff::{derivative, DiffMethod};
+
+    // Composite function touching every elementary function.
+    fn composite<T: Real>(x: T) -> T {
+        let a = (x * x + 1.0).sqrt().ln() * x.sin() - x.cos().exp() / (x.tanh() + 2.0);
+        let b = x.atan() * x.sinh() + x.cosh().powf(0.5) - (x * 0.3).tan() + (-x).abs().powi(3);
+        a * b
+    }
+
+    for &x in [-1.3, -0.2, 0.4, 1.1].iter() {
+        let (f, df) = with_dual_derivative(composite)(x);
+        let numeric = derivative(composite, x, DiffMethod::Richardson, 0.0);
+        println!("x: {} f: {} df: {} numeric: {} ({:e})", x, f, df, numeric.df, numeric.error);
+        assert_eq!(f, composite(x));
+        assert_float_absolute_eq!(df, numeric.df, 1.0e-9);
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_gradient_minimizers() {
+    use super::{brent_df_search, lbfgsb};
+
+    fn cosine<T: Real>(x: T) -> T { x.cos() }
+    let (xmin, _, _) = brent_df_search(with_dual_derivative(cosine), 0.01, 1.0, 0.0, 0);
+    assert_float_relative_eq!(xmin, std::f64::consts::PI, 1.0e-8);
+
+    fn rosenbrock<T: Real>(x: &[T]) -> T {
+        let mut f = T::from_f64(0.0);
+        for i in 0..x.len() - 1 {
+            f += (-x[i] + 1.0).powi(2) + (x[i + 1] - x[i] * x[i]).powi(2) * 100.0;
+        }
+        f
+    }
+    let n = 6;
+    let res = lbfgsb(with_dual_gradient(rosenbrock), &vec![-1.0; n],
+        &vec![f64::NEG_INFINITY; n], &vec![f64::INFINITY; n], 0, 1.0e-10, 0);
+    println!("x: {:?} f: {} iterations: {}", res.xmin, res.fmin, res.nr_iterations);
+    assert!(res.converged);
+    for xi in res.xmin.iter() {
+        assert_float_absolute_eq!(*xi, 1.0, 1.0e-8);
+    }
+}
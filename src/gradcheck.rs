@@ -0,0 +1,212 @@
+//! Checking analytic derivatives against finite differences.
+//!
+//! (c) 2023 Igor Lesik
+//! MIT license
+//!
+//! References:
+//!
+//! 1. William H. Press - Numerical recipes, the art of scientific computing.
+//!    Cambridge University Press (2007). Section 5.7.
+//!
+use super::numdiff::{derivative, DiffMethod};
+use super::sampling::{latin_hypercube, scale_to_box, Rng};
+
+/// Default relative error tolerance.
+const DEFAULT_TOLERANCE: f64 = 1.0e-6_f64;
+
+/// Result of a derivative check.
+pub struct GradCheckRes {
+    /// Relative error of every derivative component at every point.
+    pub errors: Vec<Vec<f64>>,
+    /// Largest relative error.
+    pub max_error: f64,
+    /// Point and component with the largest relative error.
+    pub worst: (usize, usize),
+    /// Whether all relative errors are within the tolerance.
+    pub passed: bool,
+}
+
+/// Check the derivative of a function in the `(f(x), f'(x))` form of
+/// [`brent_df_search`](crate::brent_df_search) at the given `points`.
+///
+/// The supplied derivative `a` is compared with the Richardson-extrapolated central
+/// difference `d` of `f`, whose own error estimate `e` is discounted:
+/// `max(|a - d| - e, 0) / max(|a|, |d|)`. The check passes if all relative errors are
+/// at most `tol` (1e-6 if 0). A derivative that is not finite has the relative error NaN
+/// and fails the check.
+///
+/// # Example
+///
+/// ```
+/// use rustamath_mnmz::check_derivative;
+/// // Wrong sign of the derivative.
+/// let cosine = |x: f64| (x.cos(), x.sin());
+///
+/// let res = check_derivative(cosine, &[0.5, 1.0, 2.0], 0.0);
+///
+/// assert!(!res.passed);
+/// assert!(check_derivative(|x: f64| (x.cos(), -x.sin()), &[0.5, 1.0, 2.0], 0.0).passed);
+/// ```
+pub fn check_derivative<F: Fn (f64) -> (f64, f64)>(fun: F, points: &[f64], tol: f64) -> GradCheckRes {
+    let errors = points.iter().map(|&x| {
+        let numeric = derivative(|t| fun(t).0, x, DiffMethod::Richardson, 0.0);
+        vec![relative_error(fun(x).1, numeric.df, numeric.error)]
+    }).collect();
+    verdict(errors, tol)
+}
+
+/// Check the gradient of a function in the `fun(x, grad) -> f(x)` form of the gradient
+/// methods at the given `points`.
+///
+/// Every component is compared as in [`check_derivative`].
+///
+/// # Example
+///
+/// ```
+/// use rustamath_mnmz::check_gradient;
+/// let rosenbrock = |x: &[f64], g: &mut [f64]| {
+///     g[0] = -2.0*(1.0 - x[0]) - 400.0*x[0]*(x[1] - x[0]*x[0]);
+///     g[1] = 100.0*(x[1] - x[0]*x[0]); // should be 200
+///     (1.0 - x[0]).powi(2) + 100.0*(x[1] - x[0]*x[0]).powi(2)
+/// };
+///
+/// let res = check_gradient(rosenbrock, &[vec![-1.2, 1.0], vec![0.5, 0.5]], 0.0);
+///
+/// assert!(!res.passed);
+/// assert_eq!(res.worst.1, 1);
+/// assert!((res.max_error - 0.5).abs() < 1.0e-6);
+/// ```
+pub fn check_gradient<F: Fn (&[f64], &mut [f64]) -> f64>(fun: F, points: &[Vec<f64>], tol: f64) -> GradCheckRes {
+    let errors = points.iter().map(|x| {
+        let n = x.len();
+        let mut grad = vec![0.0; n];
+        fun(x, &mut grad);
+        (0..n).map(|i| {
+            let numeric = derivative(|t| {
+                let mut xt = x.clone();
+                xt[i] = t;
+                fun(&xt, &mut vec![0.0; n])
+            }, x[i], DiffMethod::Richardson, 0.0);
+            relative_error(grad[i], numeric.df, numeric.error)
+        }).collect()
+    }).collect();
+    verdict(errors, tol)
+}
+
+/// Check the gradient at `nr_points` random points in the box `lower <= x <= upper`.
+///
+/// The points form a Latin hypercube design drawn with `rng`, see [`check_gradient`].
+pub fn check_gradient_random<F, R>(
+    fun: F,
+    lower: &[f64],
+    upper: &[f64],
+    nr_points: usize,
+    tol: f64,
+    rng: &mut R
+) -> GradCheckRes
+where
+    F: Fn (&[f64], &mut [f64]) -> f64,
+    R: Rng
+{
+    let mut points = latin_hypercube(nr_points, lower.len(), rng);
+    scale_to_box(&mut points, lower, upper);
+    check_gradient(fun, &points, tol)
+}
+
+fn relative_error(analytic: f64, numeric: f64, numeric_error: f64) -> f64 {
+    if !analytic.is_finite() || !numeric.is_finite() {
+        return f64::NAN;
+    }
+    let diff = ((analytic - numeric).abs() - numeric_error).max(0.0);
+    if diff == 0.0 { 0.0 } else { diff / analytic.abs().max(numeric.abs()) }
+}
+
+fn verdict(errors: Vec<Vec<f64>>, tol: f64) -> GradCheckRes {
+    let tol = if tol > 0.0 { tol } else { DEFAULT_TOLERANCE };
+    let mut worst = (0, 0);
+    let mut max_error = 0.0;
+    for (p, row) in errors.iter().enumerate() {
+        for (i, &e) in row.iter().enumerate() {
+            // NaN counts as the worst error.
+            if e > max_error || (e.is_nan() && !max_error.is_nan()) {
+                max_error = e;
+                worst = (p, i);
+            }
+        }
+    }
+    GradCheckRes { passed: max_error <= tol, errors, max_error, worst }
+}
+
+#[cfg(test)]
+#[test]
+fn test_derivative_sign_error() {
+    use super::brent_df_search;
+
+    // With the wrong sign brent_df_search is led to the wrong point.
+    let wrong = |x: f64| (x.cos(), x.sin());
+    let (xmin, _, _) = brent_df_search(wrong, 0.01, 1.0, 0.0, 0);
+    println!("xmin with the wrong derivative: {}", xmin);
+    assert!((xmin - std::f64::consts::PI).abs() > 0.1);
+
+    let res = check_derivative(wrong, &[0.3, 1.0, 2.5], 0.0);
+    println!("errors: {:?}", res.errors);
+    assert!(!res.passed);
+    assert_float_absolute_eq!(res.max_error, 2.0, 1.0e-9);
+
+    let res = check_derivative(|x: f64| (x.cos(), -x.sin()), &[0.3, 1.0, 2.5, 0.0], 1.0e-9);
+    assert!(res.passed);
+    assert!(res.errors.iter().all(|e| e[0] < 1.0e-9));
+}
+
+#[cfg(test)]
+#[test]
+fn test_random_points() {
+    use super::sampling::{SeedableRng, SplitMix64};
+
+    let quartic = |x: &[f64], g: &mut [f64]| {
+        let n = x.len();
+        let mut f = 0.0;
+        for i in 0..n {
+            g[i] = 4.0 * x[i].powi(3) + x[(i + 1) % n] + x[(i + n - 1) % n];
+            f += x[i].powi(4) + x[i] * x[(i + 1) % n];
+        }
+        f
+    };
+    let mut rng = SplitMix64::seed_from_u64(7);
+    let res = check_gradient_random(quartic, &[-2.0; 3], &[2.0; 3], 10, 1.0e-8, &mut rng);
+    println!("max error: {:e} at {:?}", res.max_error, res.worst);
+    assert_eq!(res.errors.len(), 10);
+    assert!(res.passed);
+
+    // Missing the cross term in the last component.
+    let buggy = |x: &[f64], g: &mut [f64]| {
+        let f = quartic(x, g);
+        g[2] -= x[0];
+        f
+    };
+    let res = check_gradient_random(buggy, &[-2.0; 3], &[2.0; 3], 10, 1.0e-8, &mut rng);
+    println!("max error: {:e} at {:?}", res.max_error, res.worst);
+    assert!(!res.passed);
+    assert_eq!(res.worst.1, 2);
+    assert!(res.errors.iter().all(|e| e[0] < 1.0e-8 && e[1] < 1.0e-8));
+}
+
+#[cfg(test)]
+#[test]
+fn test_not_finite() {
+    let res = check_derivative(|x: f64| (x.cos(), f64::NAN), &[0.5, 1.0], 0.0);
+    assert!(!res.passed);
+    assert!(res.max_error.is_nan());
+
+    let paraboloid = |x: &[f64], g: &mut [f64]| {
+        g[0] = 2.0 * x[0];
+        g[1] = if x[1] > 0.0 { f64::INFINITY } else { 2.0 * x[1] };
+        g[2] = 2.0 * x[2];
+        x.iter().map(|xi| xi * xi).sum()
+    };
+    let res = check_gradient(paraboloid, &[vec![1.0, -1.0, 2.0], vec![0.5, 1.0, -0.5]], 0.0);
+    println!("errors: {:?}", res.errors);
+    assert!(!res.passed);
+    assert!(res.max_error.is_nan());
+    assert_eq!(res.worst, (1, 1));
+}
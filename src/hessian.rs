@@ -0,0 +1,255 @@
+//! Hessian, curvature and covariance at a minimum.
+//!
+//! (c) 2023 Igor Lesik
+//! MIT license
+//!
+//! References:
+//!
+//! 1. William H. Press - Numerical recipes, the art of scientific computing.
+//!    Cambridge University Press (2007). Sections 10.5, 11.1, 15.5.
+//!
+use super::linalg::{Matrix, invert, symmetric_eigenvalues};
+
+/// Hessian with its eigenvalues and the covariance matrix.
+pub struct HessianRes {
+    /// Hessian, symmetric.
+    pub hessian: Vec<Vec<f64>>,
+    /// Eigenvalues of the Hessian in ascending order; all positive at a strict minimum.
+    pub eigenvalues: Vec<f64>,
+    /// Inverse of the Hessian, `None` unless the Hessian is positive definite.
+    ///
+    /// It is the covariance of the parameters when `f` is a negative log-likelihood.
+    /// For `f = chi^2` the covariance is twice this matrix.
+    pub covariance: Option<Vec<Vec<f64>>>,
+    /// Number of function evaluations.
+    pub nr_evaluations: usize,
+}
+
+/// Hessian of `f` at `x` by central finite differences.
+///
+/// - William H. Press - Numerical recipes, the art of scientific computing (2007).
+///
+/// `noise` is the relative error of the computed values of `f`, at least `f64::EPSILON`
+/// (used if 0). The step `h_i = noise^(1/4) max(|x_i|, 1)` balances the `O(h^2)` truncation
+/// error of the second differences with their rounding error. The diagonal needs `2 n`
+/// and the off-diagonal `2 n (n - 1)` evaluations, plus one at `x`.
+///
+/// # Example
+///
+/// ```
+/// use rustamath_mnmz::{amoeba, hessian};
+/// use assert_float_eq::*;
+/// let paraboloid = |x: &[f64]| 10.0*(x[0] - 1.0).powi(2) + 20.0*(x[1] - 2.0).powi(2) + 30.0;
+/// let (xmin, _, _) = amoeba(paraboloid, &[0.0, 0.0], 1.0, 1.0e-10, 500);
+///
+/// let res = hessian(paraboloid, &xmin, 0.0);
+///
+/// assert_float_absolute_eq!(res.eigenvalues[0], 20.0, 1.0e-5);
+/// assert_float_absolute_eq!(res.eigenvalues[1], 40.0, 1.0e-5);
+/// let cov = res.covariance.unwrap();
+/// assert_float_absolute_eq!(cov[0][0], 0.05, 1.0e-8);
+/// assert_float_absolute_eq!(cov[1][1], 0.025, 1.0e-8);
+/// ```
+#[allow(clippy::needless_range_loop)]
+pub fn hessian<F: Fn (&[f64]) -> f64>(fun: F, x: &[f64], noise: f64) -> HessianRes {
+    let n = x.len();
+    let noise = noise.max(f64::EPSILON);
+    // Steps exactly representable as the difference of two floating point numbers.
+    let h: Vec<f64> = x.iter().map(|&xi| (xi + noise.powf(0.25) * xi.abs().max(1.0)) - xi).collect();
+
+    let mut xt = x.to_vec();
+    let mut eval = |steps: &[(usize, f64)]| {
+        for &(i, s) in steps {
+            xt[i] = x[i] + s * h[i];
+        }
+        let f = fun(&xt);
+        for &(i, _) in steps {
+            xt[i] = x[i];
+        }
+        f
+    };
+
+    let f0 = eval(&[]);
+    let mut hess = Matrix::new(n, n);
+    for i in 0..n {
+        let d2 = (eval(&[(i, 1.0)]) - 2.0 * f0 + eval(&[(i, -1.0)])) / (h[i] * h[i]);
+        hess.set(i, i, d2);
+        for j in 0..i {
+            let d2 = (eval(&[(i, 1.0), (j, 1.0)]) - eval(&[(i, 1.0), (j, -1.0)])
+                - eval(&[(i, -1.0), (j, 1.0)]) + eval(&[(i, -1.0), (j, -1.0)])) / (4.0 * h[i] * h[j]);
+            hess.set(i, j, d2);
+            hess.set(j, i, d2);
+        }
+    }
+
+    curvature(&hess, 1 + 2 * n * n)
+}
+
+/// Hessian of `f` from a quadratic fitted to the final simplex of the downhill simplex
+/// search.
+///
+/// - William H. Press - Numerical recipes, the art of scientific computing (2007), 10.5.
+///
+/// A quadratic in `n` dimensions has `(n + 1)(n + 2) / 2` coefficients. The `n + 1`
+/// vertices of the `simplex` with their function `values` are supplemented by the
+/// `n (n + 1) / 2` midpoints of its edges, where `f` is evaluated, and the quadratic through
+/// these points is exact for a quadratic `f`. The simplex returned by
+/// [`amoeba_simplex`](crate::amoeba_simplex) is small and surrounds the minimum. With too
+/// tight a tolerance its vertices differ in `f` by little more than rounding, so the
+/// tolerance for the search should not be much below `1e-10`.
+///
+/// Returns `None` if the simplex is degenerate.
+///
+/// # Example
+///
+/// ```
+/// use rustamath_mnmz::{amoeba_simplex, hessian_from_simplex};
+/// use assert_float_eq::*;
+/// let paraboloid = |x: &[f64]| 10.0*(x[0] - 1.0).powi(2) + 20.0*(x[1] - 2.0).powi(2) + 30.0;
+/// let res = amoeba_simplex(paraboloid, &[0.0, 0.0], 1.0, 1.0e-10, 500);
+///
+/// let curv = hessian_from_simplex(paraboloid, &res.simplex, &res.values).unwrap();
+///
+/// assert_eq!(curv.nr_evaluations, 3);
+/// assert_float_relative_eq!(curv.hessian[0][0], 20.0, 1.0e-4);
+/// assert_float_relative_eq!(curv.hessian[1][1], 40.0, 1.0e-4);
+/// ```
+#[allow(clippy::needless_range_loop)]
+pub fn hessian_from_simplex<F: Fn (&[f64]) -> f64>(
+    fun: F,
+    simplex: &[Vec<f64>],
+    values: &[f64]
+) -> Option<HessianRes>
+{
+    let n = simplex.len() - 1;
+    let x0 = &simplex[0];
+    let f0 = values[0];
+    let mid = |a: &[f64], b: &[f64]| -> Vec<f64> { a.iter().zip(b.iter()).map(|(u, v)| 0.5 * (u + v)).collect() };
+
+    // Edges as columns: x = x0 + D a.
+    let mut d = Matrix::new(n, n);
+    for i in 0..n {
+        for k in 0..n {
+            d.set(k, i, simplex[i + 1][k] - x0[k]);
+        }
+    }
+    let dinv = invert(&d)?;
+
+    // Quadratic q(a) = f0 + g^T a + a^T B a / 2 in the edge coordinates, B = D^T H D.
+    let mut g = vec![0.0; n];
+    let mut b = Matrix::new(n, n);
+    for i in 0..n {
+        let m0i = fun(&mid(x0, &simplex[i + 1]));
+        b.set(i, i, 4.0 * (values[i + 1] - 2.0 * m0i + f0));
+        g[i] = 4.0 * m0i - 3.0 * f0 - values[i + 1];
+    }
+    for i in 0..n {
+        for j in 0..i {
+            let mij = fun(&mid(&simplex[i + 1], &simplex[j + 1]));
+            let bij = 4.0 * (mij - f0 - 0.5 * (g[i] + g[j])) - 0.5 * (b.get(i, i) + b.get(j, j));
+            b.set(i, j, bij);
+            b.set(j, i, bij);
+        }
+    }
+
+    // H = D^-T B D^-1
+    let mut bd = Matrix::new(n, n);
+    for i in 0..n {
+        for j in 0..n {
+            bd.set(i, j, (0..n).map(|k| b.get(i, k) * dinv.get(k, j)).sum());
+        }
+    }
+    let mut hess = Matrix::new(n, n);
+    for i in 0..n {
+        for j in 0..n {
+            hess.set(i, j, (0..n).map(|k| dinv.get(k, i) * bd.get(k, j)).sum());
+        }
+    }
+
+    Some(curvature(&hess, n * (n + 1) / 2))
+}
+
+/// Symmetrize the Hessian and derive its eigenvalues and inverse.
+#[allow(clippy::needless_range_loop)]
+fn curvature(hess: &Matrix, nr_evaluations: usize) -> HessianRes {
+    let n = hess.nrows;
+    let mut h = hess.clone();
+    for i in 0..n {
+        for j in 0..i {
+            let v = 0.5 * (hess.get(i, j) + hess.get(j, i));
+            h.set(i, j, v);
+            h.set(j, i, v);
+        }
+    }
+    let eigenvalues = symmetric_eigenvalues(&h);
+    let rows = |m: &Matrix| -> Vec<Vec<f64>> { (0..n).map(|i| (0..n).map(|j| m.get(i, j)).collect()).collect() };
+    let covariance = if eigenvalues.first().is_some_and(|&e| e > 0.0) {
+        invert(&h).map(|inv| rows(&inv))
+    }
+    else {
+        None
+    };
+
+    HessianRes { hessian: rows(&h), eigenvalues, covariance, nr_evaluations }
+}
+
+#[cfg(test)]
+#[test]
+fn test_eigenvalues() {
+    let mut a = Matrix::new(3, 3);
+    let v = [[4.0, 1.0, -2.0], [1.0, 2.0, 0.0], [-2.0, 0.0, 3.0]];
+    for (i, row) in v.iter().enumerate() {
+        for (j, &vij) in row.iter().enumerate() {
+            a.set(i, j, vij);
+        }
+    }
+    let eig = symmetric_eigenvalues(&a);
+    println!("{:?}", eig);
+    // Invariants: trace and determinant.
+    assert_float_absolute_eq!(eig.iter().sum::<f64>(), 9.0, 1.0e-12);
+    assert_float_absolute_eq!(eig.iter().product::<f64>(), 24.0 - 3.0 - 8.0, 1.0e-12);
+    for &e in eig.iter() {
+        let det = (4.0 - e) * ((2.0 - e) * (3.0 - e)) - (3.0 - e) - 4.0 * (2.0 - e);
+        assert_float_absolute_eq!(det, 0.0, 1.0e-10);
+    }
+    assert!(eig[0] < eig[1] && eig[1] < eig[2]);
+
+    // Saddle point has no covariance.
+    let res = hessian(|x: &[f64]| x[0] * x[0] - x[1] * x[1] + x[0] * x[1], &[0.0, 0.0], 0.0);
+    println!("{:?} {:?}", res.hessian, res.eigenvalues);
+    assert!(res.eigenvalues[0] < 0.0 && res.eigenvalues[1] > 0.0);
+    assert!(res.covariance.is_none());
+}
+
+#[cfg(test)]
+#[test]
+fn test_line_fit_covariance() {
+    use super::simplex::amoeba_simplex;
+
+    // Straight line fit, f = chi^2 / 2 whose inverse Hessian is the covariance (A^T A)^-1 sigma^2.
+    let t = [0.0, 1.0, 2.0, 3.0, 4.0, 5.0];
+    let y = [1.1, 2.9, 5.2, 7.1, 8.8, 11.2];
+    let sigma = 0.2;
+    let chi2 = |p: &[f64]| t.iter().zip(y.iter())
+        .map(|(ti, yi)| ((yi - p[0] - p[1] * ti) / sigma).powi(2)).sum::<f64>() / 2.0;
+
+    let (s, st, stt) = (t.len() as f64, t.iter().sum::<f64>(), t.iter().map(|ti| ti * ti).sum::<f64>());
+    let det = s * stt - st * st;
+    let exact = [[stt / det, -st / det], [-st / det, s / det]];
+
+    let res = amoeba_simplex(chi2, &[0.0, 1.0], 0.5, 1.0e-10, 1000);
+    let fd = hessian(chi2, &res.xmin, 0.0);
+    let fit = hessian_from_simplex(chi2, &res.simplex, &res.values).unwrap();
+    println!("min: {:?} finite differences: {:?} simplex fit: {:?} (evaluations {} and {})",
+        res.xmin, fd.covariance, fit.covariance, fd.nr_evaluations, fit.nr_evaluations);
+
+    let cov_fd = fd.covariance.unwrap();
+    let cov_fit = fit.covariance.unwrap();
+    for i in 0..2 {
+        for j in 0..2 {
+            let e = exact[i][j] * sigma * sigma;
+            assert_float_relative_eq!(cov_fd[i][j], e, 1.0e-6);
+            assert_float_relative_eq!(cov_fit[i][j], e, 1.0e-4);
+        }
+    }
+}
@@ -0,0 +1,288 @@
+//! Primal-dual interior-point method for linear programs in standard form.
+//!
+//! (c) 2023 Igor Lesik
+//! MIT license
+//!
+//! References:
+//!
+//! 1. Sanjay Mehrotra - On the implementation of a primal-dual interior point method.
+//!    SIAM J. Optimization 2 (1992).
+//! 2. Erling D. Andersen, Knud D. Andersen - The MOSEK interior point optimizer for linear
+//!    programming: an implementation of the homogeneous algorithm.
+//!    High Performance Optimization, Kluwer (2000).
+//! 3. Stephen J. Wright - Primal-Dual Interior-Point Methods. SIAM (1997).
+//!
+use super::linalg::{Matrix, cholesky, cholesky_solve};
+use super::lp::LpStatus;
+
+/// Smallest relative tolerance of the stopping tests.
+const MIN_TOLERANCE: f64 = 1.0e-12_f64;
+
+/// Fraction of the step to the boundary of the positive orthant.
+const STEP_FRACTION: f64 = 0.99995_f64;
+
+/// Result of the interior-point method.
+pub struct IpmRes {
+    /// Outcome of the solve; `Infeasible` and `Unbounded` are certified by the embedding.
+    pub status: LpStatus,
+    /// Primal solution.
+    pub x: Vec<f64>,
+    /// Dual solution, `A^T y + s = c`.
+    pub y: Vec<f64>,
+    /// Dual slacks (reduced costs) `s = c - A^T y`.
+    pub s: Vec<f64>,
+    /// Objective value `c^T x`.
+    pub fun: f64,
+    /// Duality gap `|c^T x - b^T y|`.
+    pub gap: f64,
+    /// Number of predictor-corrector iterations.
+    pub nr_iterations: usize,
+}
+
+/// Primal-dual interior-point method (Mehrotra predictor-corrector) with the homogeneous
+/// self-dual embedding.
+///
+/// - Sanjay Mehrotra - On the implementation of a primal-dual interior point method (1992).
+/// - Erling D. Andersen, Knud D. Andersen - The MOSEK interior point optimizer for linear
+///   programming (2000).
+///
+/// Solves the linear program in standard form
+///
+/// ```text
+/// minimize c^T x  subject to  A x = b,  x >= 0
+/// ```
+///
+/// together with its dual `maximize b^T y  subject to  A^T y + s = c,  s >= 0`, where `a` is
+/// given by rows and should have full row rank.
+///
+/// The primal and dual are embedded in one homogeneous problem in `(x, y, s, tau, kappa)`
+/// that always has a solution: with `tau > 0` it scales to the optimum, with `kappa > 0` it
+/// is a certificate that the problem is infeasible (`b^T y > 0`) or unbounded (`c^T x < 0`).
+/// Every iteration solves the normal equations `A X S^-1 A^T` by Cholesky decomposition,
+/// once for the affine-scaling predictor and once for the centering corrector.
+///
+/// Stops when the relative primal and dual residuals and the relative duality gap are below
+/// `tol`, or after `max_iterations` iterations (100 if 0).
+///
+/// # Example
+///
+/// ```
+/// use rustamath_mnmz::{interior_point, LpStatus};
+/// use assert_float_eq::*;
+/// // maximize 3x + 5y  subject to  x <= 4,  2y <= 12,  3x + 2y <= 18, with slack variables
+/// let a = [
+///     vec![1.0, 0.0, 1.0, 0.0, 0.0],
+///     vec![0.0, 2.0, 0.0, 1.0, 0.0],
+///     vec![3.0, 2.0, 0.0, 0.0, 1.0],
+/// ];
+/// let res = interior_point(&[-3.0, -5.0, 0.0, 0.0, 0.0], &a, &[4.0, 12.0, 18.0], 1.0e-10, 0);
+///
+/// assert_eq!(res.status, LpStatus::Optimal);
+/// assert_float_absolute_eq!(res.x[0], 2.0, 1.0e-8);
+/// assert_float_absolute_eq!(res.x[1], 6.0, 1.0e-8);
+/// assert_float_absolute_eq!(res.fun, -36.0, 1.0e-8);
+/// assert_float_absolute_eq!(res.y[1], -1.5, 1.0e-8);
+/// assert!(res.gap < 1.0e-8);
+/// ```
+#[allow(clippy::needless_range_loop)]
+pub fn interior_point(
+    c: &[f64],
+    a: &[Vec<f64>],
+    b: &[f64],
+    tol: f64,
+    max_iterations: usize
+) -> IpmRes
+{
+    let tol = tol.max(MIN_TOLERANCE);
+    let max_iterations = if max_iterations < 1 { 100 } else { max_iterations };
+
+    let n = c.len();
+    let m = b.len();
+
+    let dot = |u: &[f64], v: &[f64]| u.iter().zip(v.iter()).map(|(x, y)| x * y).sum::<f64>();
+    let norm = |u: &[f64]| dot(u, u).sqrt();
+    let ax = |x: &[f64]| -> Vec<f64> { a.iter().map(|row| dot(row, x)).collect() };
+    let aty = |y: &[f64]| -> Vec<f64> { (0..n).map(|j| (0..m).map(|i| a[i][j] * y[i]).sum()).collect() };
+
+    let mut x = vec![1.0; n];
+    let mut s = vec![1.0; n];
+    let mut y = vec![0.0; m];
+    let mut tau = 1.0;
+    let mut kappa = 1.0;
+
+    // Residuals of the embedding: r_p = b tau - A x, r_d = c tau - A^T y - s,
+    // r_g = kappa + c^T x - b^T y.
+    let residuals = |x: &[f64], y: &[f64], s: &[f64], tau: f64, kappa: f64| {
+        let rp: Vec<f64> = ax(x).iter().zip(b.iter()).map(|(axi, bi)| bi * tau - axi).collect();
+        let rd: Vec<f64> = aty(y).iter().enumerate().map(|(j, atyj)| c[j] * tau - atyj - s[j]).collect();
+        let rg = kappa + dot(c, x) - dot(b, y);
+        (rp, rd, rg)
+    };
+    let (rp0, rd0, rg0) = residuals(&x, &y, &s, tau, kappa);
+    let (rp0, rd0, rg0) = (norm(&rp0).max(1.0), norm(&rd0).max(1.0), rg0.abs().max(1.0));
+    let mu0 = (dot(&x, &s) + tau * kappa) / (n + 1) as f64;
+
+    let mut status = LpStatus::MaxIterations;
+    let mut nr_iterations: usize = 0;
+
+    loop {
+        let (rp, rd, rg) = residuals(&x, &y, &s, tau, kappa);
+        let mu = (dot(&x, &s) + tau * kappa) / (n + 1) as f64;
+        let (cx, by) = (dot(c, &x), dot(b, &y));
+
+        let feasible = norm(&rp) / rp0 <= tol && norm(&rd) / rd0 <= tol && rg.abs() / rg0 <= tol;
+        if feasible && (cx - by).abs() <= tol * (tau + by.abs()) {
+            status = LpStatus::Optimal;
+            break;
+        }
+        if feasible && mu / mu0 <= tol && tau <= tol * kappa.max(1.0) {
+            status = if by > 0.0 { LpStatus::Infeasible } else { LpStatus::Unbounded };
+            break;
+        }
+        if nr_iterations >= max_iterations {
+            break;
+        }
+        nr_iterations += 1;
+
+        // Normal equations matrix A D^-1 A^T with D = X^-1 S; regularized if rank deficient.
+        let dinv: Vec<f64> = (0..n).map(|j| x[j] / s[j]).collect();
+        let mut mat = Matrix::new(m, m);
+        for i in 0..m {
+            for k in 0..=i {
+                let v: f64 = (0..n).map(|j| a[i][j] * dinv[j] * a[k][j]).sum();
+                mat.set(i, k, v);
+                mat.set(k, i, v);
+            }
+        }
+        let mut factor = cholesky(&mat);
+        let mut reg = 1.0e-14 * (0..m).fold(1.0_f64, |t, i| t.max(mat.get(i, i)));
+        while factor.is_none() && reg < 1.0 {
+            let mut regularized = mat.clone();
+            for i in 0..m {
+                regularized.set(i, i, mat.get(i, i) + reg);
+            }
+            factor = cholesky(&regularized);
+            reg *= 100.0;
+        }
+        let Some(l) = factor else { break };
+
+        // M p = b + A D^-1 c, the part of dy proportional to dtau.
+        let mut p = ax(&(0..n).map(|j| dinv[j] * c[j]).collect::<Vec<f64>>());
+        for i in 0..m {
+            p[i] += b[i];
+        }
+        cholesky_solve(&l, &mut p);
+        let atp = aty(&p);
+        let v: Vec<f64> = (0..n).map(|j| dinv[j] * (atp[j] - c[j])).collect();
+        let denominator = dot(b, &p) - dot(c, &v) + kappa / tau;
+
+        // Newton step of the embedding for the complementarity targets
+        // X S e -> rxs and tau kappa -> rtk, with the residuals scaled by eta.
+        let newton = |eta: f64, rxs: &[f64], rtk: f64| {
+            let w: Vec<f64> = (0..n).map(|j| dinv[j] * (eta * rd[j] - rxs[j] / x[j])).collect();
+            let mut q = ax(&w);
+            for i in 0..m {
+                q[i] += eta * rp[i];
+            }
+            cholesky_solve(&l, &mut q);
+            let atq = aty(&q);
+            let u: Vec<f64> = (0..n).map(|j| dinv[j] * atq[j] - w[j]).collect();
+            let dtau = (eta * rg - dot(b, &q) + dot(c, &u) + rtk / tau) / denominator;
+            let dy: Vec<f64> = (0..m).map(|i| q[i] + p[i] * dtau).collect();
+            let dx: Vec<f64> = (0..n).map(|j| u[j] + v[j] * dtau).collect();
+            let ds: Vec<f64> = (0..n).map(|j| (rxs[j] - s[j] * dx[j]) / x[j]).collect();
+            let dkappa = (rtk - kappa * dtau) / tau;
+            (dx, dy, ds, dtau, dkappa)
+        };
+
+        let max_step = |dx: &[f64], ds: &[f64], dtau: f64, dkappa: f64| {
+            let mut alpha: f64 = 1.0 / STEP_FRACTION;
+            for j in 0..n {
+                if dx[j] < 0.0 { alpha = alpha.min(-x[j] / dx[j]); }
+                if ds[j] < 0.0 { alpha = alpha.min(-s[j] / ds[j]); }
+            }
+            if dtau < 0.0 { alpha = alpha.min(-tau / dtau); }
+            if dkappa < 0.0 { alpha = alpha.min(-kappa / dkappa); }
+            (STEP_FRACTION * alpha).min(1.0)
+        };
+
+        // Predictor: affine scaling step toward zero complementarity.
+        let rxs: Vec<f64> = (0..n).map(|j| -x[j] * s[j]).collect();
+        let (dx, _, ds, dtau, dkappa) = newton(1.0, &rxs, -tau * kappa);
+        let alpha = max_step(&dx, &ds, dtau, dkappa);
+        let mu_aff = ((0..n).map(|j| (x[j] + alpha * dx[j]) * (s[j] + alpha * ds[j])).sum::<f64>()
+            + (tau + alpha * dtau) * (kappa + alpha * dkappa)) / (n + 1) as f64;
+        let sigma = (mu_aff / mu).powi(3).min(1.0);
+
+        // Corrector: centering toward sigma mu and second order correction.
+        let rxs: Vec<f64> = (0..n).map(|j| -x[j] * s[j] + sigma * mu - dx[j] * ds[j]).collect();
+        let rtk = -tau * kappa + sigma * mu - dtau * dkappa;
+        let (dx, dy, ds, dtau, dkappa) = newton(1.0 - sigma, &rxs, rtk);
+        let alpha = max_step(&dx, &ds, dtau, dkappa);
+
+        for j in 0..n {
+            x[j] += alpha * dx[j];
+            s[j] += alpha * ds[j];
+        }
+        for i in 0..m {
+            y[i] += alpha * dy[i];
+        }
+        tau += alpha * dtau;
+        kappa += alpha * dkappa;
+    }
+
+    if status != LpStatus::Infeasible && status != LpStatus::Unbounded {
+        x.iter_mut().chain(y.iter_mut()).chain(s.iter_mut()).for_each(|v| *v /= tau);
+    }
+    let fun = dot(c, &x);
+    let gap = (fun - dot(b, &y)).abs();
+
+    IpmRes { status, x, y, s, fun, gap, nr_iterations }
+}
+
+#[cfg(test)]
+#[test]
+fn test_random_dense() {
+    use super::linprog;
+    use super::sampling::{Rng, SeedableRng, SplitMix64};
+
+    // Feasible and dual feasible by construction: b = A x0 and c = A^T y0 + s0 with x0, s0 > 0.
+    let (m, n) = (12, 30);
+    let mut rng = SplitMix64::seed_from_u64(7);
+    let a: Vec<Vec<f64>> = (0..m).map(|_| (0..n).map(|_| rng.uniform(-1.0, 1.0)).collect()).collect();
+    let x0: Vec<f64> = (0..n).map(|_| rng.uniform(0.0, 1.0)).collect();
+    let y0: Vec<f64> = (0..m).map(|_| rng.uniform(-1.0, 1.0)).collect();
+    let b: Vec<f64> = a.iter().map(|row| row.iter().zip(x0.iter()).map(|(r, x)| r * x).sum()).collect();
+    let c: Vec<f64> = (0..n)
+        .map(|j| (0..m).map(|i| a[i][j] * y0[i]).sum::<f64>() + rng.uniform(0.0, 1.0))
+        .collect();
+
+    let res = interior_point(&c, &a, &b, 1.0e-10, 0);
+    let lp = linprog(&c, &[], &[], &a, &b, 0);
+
+    println!("ipm: {} gap: {} iterations: {}, simplex: {} pivots: {}",
+        res.fun, res.gap, res.nr_iterations, lp.fun, lp.nr_iterations);
+
+    assert_eq!(res.status, LpStatus::Optimal);
+    assert_float_relative_eq!(res.fun, lp.fun, 1.0e-8);
+    assert!(res.gap < 1.0e-8 * (1.0 + res.fun.abs()));
+    assert!(res.x.iter().chain(res.s.iter()).all(|&v| v >= 0.0));
+    for i in 0..m {
+        let ax: f64 = (0..n).map(|j| a[i][j] * res.x[j]).sum();
+        assert_float_absolute_eq!(ax, b[i], 1.0e-8);
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_infeasible_unbounded() {
+    // x1 + x2 = -1 has no solution with x >= 0.
+    let res = interior_point(&[1.0, 1.0], &[vec![1.0, 1.0]], &[-1.0], 1.0e-8, 0);
+    println!("status: {:?} iterations: {}", res.status, res.nr_iterations);
+    assert_eq!(res.status, LpStatus::Infeasible);
+
+    // minimize -x1 with x1 = x2 is unbounded.
+    let res = interior_point(&[-1.0, 0.0], &[vec![1.0, -1.0]], &[0.0], 1.0e-8, 0);
+    println!("status: {:?} iterations: {}", res.status, res.nr_iterations);
+    assert_eq!(res.status, LpStatus::Unbounded);
+}
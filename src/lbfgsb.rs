@@ -0,0 +1,432 @@
+//! Limited-memory BFGS with simple bounds (L-BFGS-B).
+//!
+//! (c) 2023 Igor Lesik
+//! MIT license
+//!
+//! References:
+//!
+//! 1. Richard H. Byrd, Peihuang Lu, Jorge Nocedal, Ciyou Zhu - A limited memory algorithm
+//!    for bound constrained optimization. SIAM J. Sci. Comput. 16 (1995).
+//! 2. Richard H. Byrd, Jorge Nocedal, Robert B. Schnabel - Representations of quasi-Newton
+//!    matrices and their use in limited memory methods. Math. Programming 63 (1994).
+//!
+use std::collections::VecDeque;
+use super::linalg::{Matrix, invert, lu_solve};
+use super::bounds::{ActiveBound, project, active_bounds};
+
+/// Sufficient decrease parameter of the Armijo line search.
+const ARMIJO: f64 = 1.0e-4_f64;
+
+/// Result of L-BFGS-B.
+pub struct LbfgsbRes {
+    /// Location of the minimum.
+    pub xmin: Vec<f64>,
+    /// Function value at `xmin`.
+    pub fmin: f64,
+    /// Bounds active at `xmin`.
+    pub active: Vec<ActiveBound>,
+    /// Infinity norm of the projected gradient `P(x - g) - x` at `xmin`.
+    pub pg_norm: f64,
+    /// Whether the projected gradient norm fell below the tolerance.
+    pub converged: bool,
+    /// Number of iterations.
+    pub nr_iterations: usize,
+    /// Number of function and gradient evaluations.
+    pub nr_evaluations: usize,
+}
+
+/// Compact representation `B = theta I - W M W^T` of the L-BFGS matrix,
+/// with `W = [Y, theta S]` and `M = [[-D, L^T], [L, theta S^T S]]^-1`.
+struct CompactBfgs {
+    s: VecDeque<Vec<f64>>,
+    y: VecDeque<Vec<f64>>,
+    theta: f64,
+    m: Matrix,
+}
+
+impl CompactBfgs {
+    fn new() -> Self {
+        CompactBfgs { s: VecDeque::new(), y: VecDeque::new(), theta: 1.0, m: Matrix::new(0, 0) }
+    }
+
+    fn len(&self) -> usize {
+        self.s.len()
+    }
+
+    fn clear(&mut self) {
+        *self = CompactBfgs::new();
+    }
+
+    /// Add the pair (s, y), dropping the oldest beyond `memory` pairs.
+    /// Returns `false` if `M` could not be formed and the memory was cleared.
+    fn push(&mut self, s: Vec<f64>, y: Vec<f64>, memory: usize) -> bool {
+        let dot = |a: &[f64], b: &[f64]| a.iter().zip(b.iter()).map(|(x, y)| x * y).sum::<f64>();
+        self.theta = dot(&y, &y) / dot(&s, &y);
+        self.s.push_back(s);
+        self.y.push_back(y);
+        if self.s.len() > memory {
+            self.s.pop_front();
+            self.y.pop_front();
+        }
+
+        let k = self.len();
+        let mut minv = Matrix::new(2 * k, 2 * k);
+        for i in 0..k {
+            for j in 0..k {
+                let sy = dot(&self.s[i], &self.y[j]);
+                if i == j {
+                    minv.set(i, i, -sy);
+                }
+                else if i > j {
+                    minv.set(k + i, j, sy);
+                    minv.set(j, k + i, sy);
+                }
+                minv.set(k + i, k + j, self.theta * dot(&self.s[i], &self.s[j]));
+            }
+        }
+        match invert(&minv) {
+            Some(m) => { self.m = m; true }
+            None => { self.clear(); false }
+        }
+    }
+
+    /// `W^T v` restricted to the indices `idx`.
+    fn wt(&self, v: &[f64], idx: &[usize]) -> Vec<f64> {
+        let k = self.len();
+        let mut p = vec![0.0; 2 * k];
+        for j in 0..k {
+            p[j] = idx.iter().map(|&i| self.y[j][i] * v[i]).sum();
+            p[k + j] = self.theta * idx.iter().map(|&i| self.s[j][i] * v[i]).sum::<f64>();
+        }
+        p
+    }
+
+    /// `M p`.
+    fn mp(&self, p: &[f64]) -> Vec<f64> {
+        (0..p.len()).map(|i| (0..p.len()).map(|j| self.m.get(i, j) * p[j]).sum()).collect()
+    }
+
+    /// Add `scale W q` to `out` at the indices `idx`.
+    fn add_w(&self, q: &[f64], scale: f64, idx: &[usize], out: &mut [f64]) {
+        let k = self.len();
+        for &i in idx {
+            let wq: f64 = (0..k).map(|j| self.y[j][i] * q[j] + self.theta * self.s[j][i] * q[k + j]).sum();
+            out[i] += scale * wq;
+        }
+    }
+
+    /// `B v`.
+    fn bv(&self, v: &[f64]) -> Vec<f64> {
+        let all: Vec<usize> = (0..v.len()).collect();
+        let mut out: Vec<f64> = v.iter().map(|vi| self.theta * vi).collect();
+        if self.len() > 0 {
+            let mwv = self.mp(&self.wt(v, &all));
+            self.add_w(&mwv, -1.0, &all, &mut out);
+        }
+        out
+    }
+}
+
+/// L-BFGS-B minimization with bounds `lower[i] <= x[i] <= upper[i]`.
+///
+/// - Richard H. Byrd, Peihuang Lu, Jorge Nocedal, Ciyou Zhu - A limited memory algorithm
+///   for bound constrained optimization (1995).
+///
+/// The function `fun(x, g)` returns `f(x)` and stores the gradient in `g`. Bounds may be
+/// infinite.
+///
+/// Each iteration builds the quadratic model of `f` with the compact L-BFGS matrix of the
+/// last `memory` steps (10 if 0), finds the generalized Cauchy point, the first local
+/// minimizer of the model along the projected steepest descent path `P(x - t g)`, and then
+/// minimizes the model over the variables that are not at a bound there, solving the reduced
+/// system by the Sherman-Morrison-Woodbury formula. The step toward that point is taken
+/// with a backtracking Armijo line search; it stays inside the box, so `fun` is never
+/// evaluated outside of it.
+///
+/// Stops when the infinity norm of the projected gradient `P(x - g) - x` is below `tol`,
+/// or after `max_iterations` iterations (1000 if 0).
+///
+/// # Example
+///
+/// ```
+/// use rustamath_mnmz::lbfgsb;
+/// use rustamath_mnmz::bounds::ActiveBound;
+/// use assert_float_eq::*;
+/// // Rosenbrock function with x <= 0.5, minimum at (0.5, 0.25).
+/// let rosenbrock = |x: &[f64], g: &mut [f64]| {
+///     g[0] = -2.0*(1.0 - x[0]) - 400.0*x[0]*(x[1] - x[0]*x[0]);
+///     g[1] = 200.0*(x[1] - x[0]*x[0]);
+///     (1.0 - x[0]).powi(2) + 100.0*(x[1] - x[0]*x[0]).powi(2)
+/// };
+///
+/// let res = lbfgsb(rosenbrock, &[-1.2, 1.0], &[-2.0, -2.0], &[0.5, 2.0], 0, 1.0e-10, 0);
+///
+/// assert!(res.converged);
+/// assert_float_absolute_eq!(res.xmin[0], 0.5, 1.0e-10);
+/// assert_float_absolute_eq!(res.xmin[1], 0.25, 1.0e-8);
+/// assert_eq!(res.active, vec![ActiveBound::Upper, ActiveBound::Free]);
+/// ```
+#[allow(clippy::needless_range_loop)]
+pub fn lbfgsb<F: Fn (&[f64], &mut [f64]) -> f64>(
+    fun: F,
+    point: &[f64],
+    lower: &[f64],
+    upper: &[f64],
+    memory: usize,
+    tol: f64,
+    max_iterations: usize
+) -> LbfgsbRes
+{
+    let memory = if memory < 1 { 10 } else { memory };
+    let max_iterations = if max_iterations < 1 { 1000 } else { max_iterations };
+    let n = point.len();
+
+    let dot = |a: &[f64], b: &[f64]| a.iter().zip(b.iter()).map(|(x, y)| x * y).sum::<f64>();
+    let pg_norm = |x: &[f64], g: &[f64]| {
+        (0..n).fold(0.0_f64, |s, i| s.max(((x[i] - g[i]).max(lower[i]).min(upper[i]) - x[i]).abs()))
+    };
+
+    let mut x = point.to_vec();
+    project(&mut x, lower, upper);
+    let mut g = vec![0.0; n];
+    let mut fx = fun(&x, &mut g);
+    let mut nr_evaluations: usize = 1;
+
+    let mut bfgs = CompactBfgs::new();
+    let mut converged = false;
+    let mut nr_iterations: usize = 0;
+
+    while nr_iterations < max_iterations {
+        if pg_norm(&x, &g) <= tol {
+            converged = true;
+            break;
+        }
+        nr_iterations += 1;
+
+        // Generalized Cauchy point. Variable i reaches its bound along -g at breakpoint t[i].
+        let t: Vec<f64> = (0..n)
+            .map(|i| {
+                if g[i] < 0.0 { (x[i] - upper[i]) / g[i] }
+                else if g[i] > 0.0 { (x[i] - lower[i]) / g[i] }
+                else { f64::INFINITY }
+            })
+            .collect();
+        let mut d: Vec<f64> = (0..n).map(|i| if t[i] > 0.0 { -g[i] } else { 0.0 }).collect();
+        let mut order: Vec<usize> = (0..n).filter(|&i| t[i] > 0.0 && t[i].is_finite()).collect();
+        order.sort_by(|&a, &b| t[a].total_cmp(&t[b]));
+
+        let mut xcp = x.clone();
+        let mut t_old = 0.0;
+        let mut next = 0;
+        loop {
+            // Model derivatives along d on the current segment: f' = g.d + (x_cp - x).B d,
+            // f'' = d.B d.
+            let bd = bfgs.bv(&d);
+            let z: Vec<f64> = (0..n).map(|i| xcp[i] - x[i]).collect();
+            let fp = dot(&g, &d) + dot(&z, &bd);
+            let fpp = dot(&d, &bd);
+            if fp >= 0.0 {
+                break;
+            }
+            let dt_min = if fpp > 0.0 { -fp / fpp } else { f64::INFINITY };
+            let t_next = if next < order.len() { t[order[next]] } else { f64::INFINITY };
+            let dt = t_next - t_old;
+            if dt_min < dt {
+                for i in 0..n {
+                    xcp[i] += dt_min * d[i];
+                }
+                break;
+            }
+            for i in 0..n {
+                xcp[i] += dt * d[i];
+            }
+            let b = order[next];
+            xcp[b] = if d[b] > 0.0 { upper[b] } else { lower[b] };
+            d[b] = 0.0;
+            t_old = t_next;
+            next += 1;
+        }
+        project(&mut xcp, lower, upper);
+
+        // Subspace minimization over the free variables at the Cauchy point:
+        // du = -(Z^T B Z)^-1 r with the reduced gradient r = Z^T (g + B (x_cp - x)) and
+        // (Z^T B Z)^-1 = I/theta + Z^T W (I - M W^T Z Z^T W / theta)^-1 M W^T Z / theta^2.
+        let free: Vec<usize> = (0..n).filter(|&i| lower[i] < xcp[i] && xcp[i] < upper[i]).collect();
+        let mut xbar = xcp.clone();
+        if !free.is_empty() {
+            let z: Vec<f64> = (0..n).map(|i| xcp[i] - x[i]).collect();
+            let bz = bfgs.bv(&z);
+            let mut r = vec![0.0; n];
+            for &i in free.iter() {
+                r[i] = g[i] + bz[i];
+            }
+            let theta = bfgs.theta;
+            let mut du: Vec<f64> = r.iter().map(|ri| -ri / theta).collect();
+
+            let k = bfgs.len();
+            if k > 0 {
+                let mut nmat = Matrix::new(2 * k, 2 * k);
+                let mut ej = vec![0.0; 2 * k];
+                for j in 0..2 * k {
+                    // Column j of W^T Z Z^T W.
+                    ej.iter_mut().enumerate().for_each(|(i, e)| *e = if i == j { 1.0 } else { 0.0 });
+                    let mut wej = vec![0.0; n];
+                    bfgs.add_w(&ej, 1.0, &free, &mut wej);
+                    let col = bfgs.mp(&bfgs.wt(&wej, &free));
+                    for i in 0..2 * k {
+                        nmat.set(i, j, if i == j { 1.0 } else { 0.0 } - col[i] / theta);
+                    }
+                }
+                let mut v = bfgs.mp(&bfgs.wt(&r, &free));
+                if lu_solve(&mut nmat, &mut v) {
+                    bfgs.add_w(&v, -1.0 / (theta * theta), &free, &mut du);
+                }
+            }
+
+            // Longest step toward the subspace minimizer that stays in the box.
+            let mut alpha: f64 = 1.0;
+            for &i in free.iter() {
+                if du[i] > 0.0 {
+                    alpha = alpha.min((upper[i] - xcp[i]) / du[i]);
+                }
+                else if du[i] < 0.0 {
+                    alpha = alpha.min((lower[i] - xcp[i]) / du[i]);
+                }
+            }
+            for &i in free.iter() {
+                xbar[i] = xcp[i] + alpha * du[i];
+            }
+            project(&mut xbar, lower, upper);
+        }
+
+        // Backtracking line search along d = xbar - x, feasible for steps in [0, 1].
+        let d: Vec<f64> = (0..n).map(|i| xbar[i] - x[i]).collect();
+        let slope = dot(&g, &d);
+        if slope >= 0.0 {
+            if bfgs.len() > 0 {
+                bfgs.clear();
+                continue;
+            }
+            break;
+        }
+        let dnorm = d.iter().fold(0.0_f64, |s, di| s.max(di.abs()));
+        let mut step = if bfgs.len() == 0 { (1.0 / dnorm).min(1.0) } else { 1.0 };
+        let mut xnew = vec![0.0; n];
+        let mut gnew = vec![0.0; n];
+        let mut fnew;
+        let mut accepted = false;
+        for _ in 0..40 {
+            for i in 0..n {
+                xnew[i] = x[i] + step * d[i];
+            }
+            project(&mut xnew, lower, upper);
+            fnew = fun(&xnew, &mut gnew);
+            nr_evaluations += 1;
+            if fnew <= fx + ARMIJO * step * slope {
+                accepted = true;
+                fx = fnew;
+                break;
+            }
+            step *= 0.5;
+        }
+        if !accepted {
+            if bfgs.len() > 0 {
+                bfgs.clear();
+                continue;
+            }
+            break;
+        }
+
+        let s: Vec<f64> = (0..n).map(|i| xnew[i] - x[i]).collect();
+        let y: Vec<f64> = (0..n).map(|i| gnew[i] - g[i]).collect();
+        x.copy_from_slice(&xnew);
+        g.copy_from_slice(&gnew);
+        if dot(&s, &y) > f64::EPSILON * dot(&y, &y) {
+            bfgs.push(s, y, memory);
+        }
+    }
+
+    LbfgsbRes {
+        active: active_bounds(&x, lower, upper),
+        pg_norm: pg_norm(&x, &g),
+        xmin: x,
+        fmin: fx,
+        converged,
+        nr_iterations,
+        nr_evaluations,
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_box_quadratic() {
+    // Weighted paraboloid centered at (-1, 0.5, 2) in the unit cube.
+    let fun = |x: &[f64], g: &mut [f64]| {
+        let c = [-1.0, 0.5, 2.0];
+        let w = [1.0, 10.0, 100.0];
+        let mut f = 0.0;
+        for i in 0..3 {
+            g[i] = 2.0 * w[i] * (x[i] - c[i]);
+            f += w[i] * (x[i] - c[i]).powi(2);
+        }
+        f
+    };
+
+    let res = lbfgsb(fun, &[0.5, 0.1, 0.2], &[0.0; 3], &[1.0; 3], 5, 1.0e-10, 0);
+
+    println!("x: {:?} f: {} pg: {} iterations: {} evaluations: {}",
+        res.xmin, res.fmin, res.pg_norm, res.nr_iterations, res.nr_evaluations);
+
+    assert!(res.converged);
+    assert!(res.pg_norm <= 1.0e-10);
+    assert_float_absolute_eq!(res.xmin[0], 0.0, 1.0e-12);
+    assert_float_absolute_eq!(res.xmin[1], 0.5, 1.0e-10);
+    assert_float_absolute_eq!(res.xmin[2], 1.0, 1.0e-12);
+    assert_eq!(res.active, vec![ActiveBound::Lower, ActiveBound::Free, ActiveBound::Upper]);
+}
+
+#[cfg(test)]
+#[test]
+fn test_extended_rosenbrock() {
+    // Unbounded extended Rosenbrock function in 10 dimensions, minimum at (1, ..., 1).
+    let fun = |x: &[f64], g: &mut [f64]| {
+        let mut f = 0.0;
+        g.iter_mut().for_each(|gi| *gi = 0.0);
+        for i in (0..x.len()).step_by(2) {
+            let t1 = 1.0 - x[i];
+            let t2 = 10.0 * (x[i + 1] - x[i] * x[i]);
+            g[i + 1] += 20.0 * t2;
+            g[i] -= 2.0 * (10.0 * 2.0 * x[i] * t2 + t1);
+            f += t1 * t1 + t2 * t2;
+        }
+        f
+    };
+    let n = 10;
+    let point: Vec<f64> = (0..n).map(|i| if i % 2 == 0 { -1.2 } else { 1.0 }).collect();
+
+    let res = lbfgsb(fun, &point, &vec![f64::NEG_INFINITY; n], &vec![f64::INFINITY; n], 0, 1.0e-8, 0);
+
+    println!("f: {} pg: {} iterations: {} evaluations: {}",
+        res.fmin, res.pg_norm, res.nr_iterations, res.nr_evaluations);
+
+    assert!(res.converged);
+    for xi in res.xmin.iter() {
+        assert_float_absolute_eq!(*xi, 1.0, 1.0e-7);
+    }
+    assert!(res.active.iter().all(|&a| a == ActiveBound::Free));
+}
+
+#[cfg(test)]
+#[test]
+fn test_max_iterations() {
+    let rosenbrock = |x: &[f64], g: &mut [f64]| {
+        g[0] = -2.0*(1.0 - x[0]) - 400.0*x[0]*(x[1] - x[0]*x[0]);
+        g[1] = 200.0*(x[1] - x[0]*x[0]);
+        (1.0 - x[0]).powi(2) + 100.0*(x[1] - x[0]*x[0]).powi(2)
+    };
+
+    let res = lbfgsb(rosenbrock, &[-1.2, 1.0], &[-2.0, -2.0], &[2.0, 2.0], 0, 1.0e-12, 5);
+
+    assert!(!res.converged);
+    assert_eq!(res.nr_iterations, 5);
+}
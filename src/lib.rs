@@ -8,14 +8,71 @@
 //!
 pub mod bracket;
 pub use bracket::{find_bracket, BracketRes};
+#[allow(clippy::doc_lazy_continuation)] // reference list of the module docs
 pub mod golden_section;
 pub use golden_section::golden_section_search;
+#[allow(clippy::doc_lazy_continuation)] // reference list of the module docs
 pub mod brents_method;
 pub use brents_method::brent_search;
+#[allow(clippy::doc_lazy_continuation)] // reference list of the module docs
 pub mod brents_df_method;
 pub use brents_df_method::brent_df_search;
+pub mod cubic_method;
+pub use cubic_method::cubic_search;
+pub mod newtons_method;
+pub use newtons_method::newton_search;
+#[allow(clippy::doc_lazy_continuation)] // reference lists of the module and amoeba docs
 pub mod simplex;
-pub use simplex::amoeba;
+pub use simplex::{amoeba, amoeba_bounded, amoeba_simplex, AmoebaBoundedRes, AmoebaSimplexRes};
+pub mod bounds;
+pub mod basin_hopping;
+pub use basin_hopping::{basin_hopping, basin_hopping_with_rng, BasinHoppingOpts, BasinHoppingRes};
+pub mod lipschitz;
+pub use lipschitz::{lipschitz_global, LipschitzRes};
+pub mod direct;
+pub use direct::{direct, DirectVariant, DirectRes};
+pub mod bayes_opt;
+pub use bayes_opt::{bayes_opt, BayesOpt, GaussianProcess, Kernel, Acquisition};
+pub mod sampling;
+pub use sampling::{Rng, SeedableRng, SplitMix64};
+pub mod lp;
+pub use lp::{linprog, LpStatus, LpRes};
+pub mod qp;
+pub use qp::{quadprog, QpStatus, QpRes};
+pub mod interior_point;
+pub use interior_point::{interior_point, IpmRes};
+pub mod milp;
+pub use milp::{milp, MilpOpts, MilpRes, MilpStatus, NodeSelection};
+pub mod constrained;
+pub use constrained::{augmented_lagrangian, AugLagOpts, AugLagRes};
+pub mod cobyla;
+pub use cobyla::{cobyla, CobylaRes};
+pub mod sqp;
+pub use sqp::{sqp, SqpRes};
+pub mod mirror_descent;
+pub use mirror_descent::{exponentiated_gradient, project_onto_simplex, SimplexRes};
+pub mod numdiff;
+pub use numdiff::{derivative, gradient, with_derivative, with_gradient, DiffMethod, DiffRes, GradientRes};
+pub mod lbfgsb;
+pub use lbfgsb::{lbfgsb, LbfgsbRes};
+pub mod newton_cg;
+pub use newton_cg::{newton_cg, with_hessp, NewtonCgRes};
+pub mod real;
+pub use real::Real;
+pub mod dual;
+pub use dual::{dual_gradient, with_dual_derivative, with_dual_gradient, Dual};
+pub mod tape;
+pub use tape::{reverse_gradient, with_reverse_gradient, Tape, Var};
+pub mod complex_step;
+pub use complex_step::{complex_step_gradient, with_complex_step, Complex};
+pub mod hessian;
+pub use hessian::{hessian, hessian_from_simplex, HessianRes};
+pub mod gradcheck;
+pub use gradcheck::{check_derivative, check_gradient, check_gradient_random, GradCheckRes};
+pub mod sparse_jacobian;
+pub use sparse_jacobian::{color_columns, sparse_jacobian, SparseJacobianRes};
+
+mod linalg;
 
 #[cfg(test)]
 #[macro_use]
@@ -0,0 +1,223 @@
+//! Dense linear algebra shared by the minimization methods.
+//!
+//! (c) 2023 Igor Lesik
+//! MIT license
+//!
+//! References:
+//!
+//! 1. William H. Press - Numerical recipes, the art of scientific computing.
+//!    Cambridge University Press (2007).
+//!
+
+/// Row-major dense matrix.
+#[derive(Clone)]
+pub(crate) struct Matrix {
+    pub nrows: usize,
+    pub ncols: usize,
+    pub v: Vec<f64>,
+}
+
+impl Matrix {
+    pub fn new(nrows: usize, ncols: usize) -> Self {
+        let mut m = Matrix {
+            nrows,
+            ncols,
+            v : Vec::<f64>::with_capacity(nrows * ncols),
+        };
+        m.v.resize(nrows * ncols, 0.0);
+        m
+    }
+
+    #[inline] pub fn vpos(&self, row: usize, col: usize) -> usize {
+        row*self.ncols + col
+    }
+
+    #[inline] pub fn get(&self, row: usize, col: usize) -> f64 {
+        self.v[self.vpos(row, col)]
+    }
+
+    #[inline] pub fn set(&mut self, row: usize, col: usize, new_val: f64) -> &mut Self {
+        let vpos = self.vpos(row, col);
+        self.v[vpos] = new_val;
+        self
+    }
+
+    #[inline] pub fn swap(&mut self, row1: usize, col1: usize, row2: usize, col2: usize) {
+        let vpos1 = self.vpos(row1, col1);
+        let vpos2 = self.vpos(row2, col2);
+        self.v.swap(vpos1, vpos2);
+    }
+
+    #[inline] pub fn get_psum(&self, psum: &mut [f64]) {
+        #[allow(clippy::needless_range_loop)]
+        for j in 0..self.ncols {
+            let mut sum = 0.0;
+            for i in 0..self.nrows {
+                sum += self.get(i, j);
+            }
+            psum[j] = sum;
+        }
+    }
+}
+
+/// Cholesky decomposition `A = L L^T` of a symmetric positive-definite matrix.
+///
+/// Returns the lower triangular `L`, or `None` if `A` is not positive-definite.
+pub(crate) fn cholesky(a: &Matrix) -> Option<Matrix> {
+    let n = a.nrows;
+    let mut l = Matrix::new(n, n);
+    for i in 0..n {
+        for j in 0..=i {
+            let mut sum = a.get(i, j);
+            for k in 0..j {
+                sum -= l.get(i, k) * l.get(j, k);
+            }
+            if i == j {
+                if sum <= 0.0 {
+                    return None;
+                }
+                l.set(i, i, sum.sqrt());
+            }
+            else {
+                let x = sum / l.get(j, j);
+                l.set(i, j, x);
+            }
+        }
+    }
+    Some(l)
+}
+
+/// Solve `L y = b` in place for lower triangular `L`.
+#[allow(clippy::needless_range_loop)]
+pub(crate) fn forward_subst(l: &Matrix, b: &mut [f64]) {
+    for i in 0..l.nrows {
+        let mut sum = b[i];
+        for k in 0..i {
+            sum -= l.get(i, k) * b[k];
+        }
+        b[i] = sum / l.get(i, i);
+    }
+}
+
+/// Solve `L^T x = y` in place for lower triangular `L`.
+#[allow(clippy::needless_range_loop)]
+pub(crate) fn backward_subst(l: &Matrix, b: &mut [f64]) {
+    for i in (0..l.nrows).rev() {
+        let mut sum = b[i];
+        for k in i+1..l.nrows {
+            sum -= l.get(k, i) * b[k];
+        }
+        b[i] = sum / l.get(i, i);
+    }
+}
+
+/// Solve `L L^T x = b` in place, given the Cholesky factor `L`.
+pub(crate) fn cholesky_solve(l: &Matrix, b: &mut [f64]) {
+    forward_subst(l, b);
+    backward_subst(l, b);
+}
+
+/// Solve `A x = b` in place by Gaussian elimination with partial pivoting.
+///
+/// `A` is overwritten. Returns `false` if `A` is numerically singular.
+#[allow(clippy::needless_range_loop)]
+pub(crate) fn lu_solve(a: &mut Matrix, b: &mut [f64]) -> bool {
+    let n = a.nrows;
+    let scale = a.v.iter().fold(0.0_f64, |s, x| s.max(x.abs())).max(f64::MIN_POSITIVE);
+
+    for k in 0..n {
+        let piv = (k..n).max_by(|&i, &j| a.get(i, k).abs().total_cmp(&a.get(j, k).abs())).unwrap_or(k);
+        if a.get(piv, k).abs() <= 1.0e-13 * scale {
+            return false;
+        }
+        if piv != k {
+            for j in 0..n {
+                a.swap(k, j, piv, j);
+            }
+            b.swap(k, piv);
+        }
+        for i in k+1..n {
+            let factor = a.get(i, k) / a.get(k, k);
+            if factor != 0.0 {
+                for j in k..n {
+                    let v = a.get(i, j) - factor * a.get(k, j);
+                    a.set(i, j, v);
+                }
+                b[i] -= factor * b[k];
+            }
+        }
+    }
+
+    for i in (0..n).rev() {
+        let mut sum = b[i];
+        for j in i+1..n {
+            sum -= a.get(i, j) * b[j];
+        }
+        b[i] = sum / a.get(i, i);
+    }
+    true
+}
+
+/// Inverse of a square matrix, `None` if it is numerically singular.
+pub(crate) fn invert(a: &Matrix) -> Option<Matrix> {
+    let n = a.nrows;
+    let mut inv = Matrix::new(n, n);
+    let mut col = vec![0.0; n];
+    for j in 0..n {
+        col.iter_mut().enumerate().for_each(|(i, c)| *c = if i == j { 1.0 } else { 0.0 });
+        if !lu_solve(&mut a.clone(), &mut col) {
+            return None;
+        }
+        for (i, &c) in col.iter().enumerate() {
+            inv.set(i, j, c);
+        }
+    }
+    Some(inv)
+}
+
+/// Eigenvalues of a symmetric matrix by the cyclic Jacobi method, in ascending order.
+///
+/// Plane rotations annihilate the off-diagonal elements one by one until they are
+/// negligible compared with the diagonal.
+#[allow(clippy::needless_range_loop)]
+pub(crate) fn symmetric_eigenvalues(a: &Matrix) -> Vec<f64> {
+    const MAX_SWEEPS: usize = 50;
+    let n = a.nrows;
+    let mut a = a.clone();
+
+    for _ in 0..MAX_SWEEPS {
+        let off: f64 = (0..n).flat_map(|i| (i+1..n).map(move |j| (i, j)))
+            .map(|(i, j)| a.get(i, j) * a.get(i, j)).sum();
+        let diag: f64 = (0..n).map(|i| a.get(i, i) * a.get(i, i)).sum();
+        if off <= f64::EPSILON * f64::EPSILON * diag || off == 0.0 {
+            break;
+        }
+        for p in 0..n {
+            for q in p+1..n {
+                let apq = a.get(p, q);
+                if apq == 0.0 {
+                    continue;
+                }
+                // Rotation angle that zeroes a[p][q], smaller root for stability.
+                let theta = (a.get(q, q) - a.get(p, p)) / (2.0 * apq);
+                let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+                let c = 1.0 / (t * t + 1.0).sqrt();
+                let s = t * c;
+                for k in 0..n {
+                    let (akp, akq) = (a.get(k, p), a.get(k, q));
+                    a.set(k, p, c * akp - s * akq);
+                    a.set(k, q, s * akp + c * akq);
+                }
+                for k in 0..n {
+                    let (apk, aqk) = (a.get(p, k), a.get(q, k));
+                    a.set(p, k, c * apk - s * aqk);
+                    a.set(q, k, s * apk + c * aqk);
+                }
+            }
+        }
+    }
+
+    let mut eig: Vec<f64> = (0..n).map(|i| a.get(i, i)).collect();
+    eig.sort_by(|x, y| x.total_cmp(y));
+    eig
+}
@@ -0,0 +1,182 @@
+//! Global minimization of a Lipschitz function on an interval (Piyavskii-Shubert).
+//!
+//! (c) 2023 Igor Lesik
+//! MIT license
+//!
+//! References:
+//!
+//! 1. S. A. Piyavskii - An algorithm for finding the absolute extremum of a function.
+//!    USSR Computational Mathematics and Mathematical Physics 12 (1972).
+//! 2. Bruno O. Shubert - A sequential method seeking the global maximum of a function.
+//!    SIAM Journal on Numerical Analysis 9 (1972).
+//! 3. Roman G. Strongin, Yaroslav D. Sergeyev - Global Optimization with Non-Convex
+//!    Constraints. Springer (2000).
+//!
+
+/// Smallest gap between the best value and the lower bound we try to reach.
+const MIN_TOLERANCE: f64 = 3.0e-8_f64;
+
+/// Reliability factor applied to the estimated Lipschitz constant.
+const ADAPTIVE_FACTOR: f64 = 1.5_f64;
+
+/// Estimated Lipschitz constant is never taken smaller than this.
+const MIN_LIPSCHITZ: f64 = 1.0e-8_f64;
+
+/// Result of the Lipschitz global search.
+pub struct LipschitzRes {
+    /// Location of the global minimum.
+    pub xmin: f64,
+    /// f(xmin)
+    pub fmin: f64,
+    /// Lower bound of `f` on the interval, certified when the Lipschitz constant is valid.
+    pub lower_bound: f64,
+    /// Lipschitz constant used on the last iteration.
+    pub lipschitz: f64,
+    /// Number of function evaluations.
+    pub nr_evaluations: usize,
+}
+
+/// Piyavskii-Shubert global minimization on the interval `[a, b]`.
+///
+/// - S. A. Piyavskii - An algorithm for finding the absolute extremum of a function (1972).
+/// - Bruno O. Shubert - A sequential method seeking the global maximum of a function (1972).
+///
+/// If `|f(x) - f(y)| <= L |x - y|` on `[a, b]`, the saw-tooth function
+/// `max_i f(x_i) - L |x - x_i|` is below `f` everywhere. The method repeatedly evaluates `f`
+/// where the saw-tooth is lowest, which gives both the next trial point and a lower bound
+/// of `f` on the whole interval. The search stops when the best value found is within `tol`
+/// of the lower bound or after `max_iterations` evaluations.
+///
+/// If `lipschitz` is not positive, the constant is estimated adaptively from the largest
+/// slope between neighboring trial points times a reliability factor; in that case the lower
+/// bound is only as trustworthy as the estimate.
+///
+/// # Example
+///
+/// ```
+/// use rustamath_mnmz::lipschitz_global;
+/// use assert_float_eq::*;
+/// // Several local minima on [2.7, 7.5], the global one at 5.145735.
+/// let fun = |x: f64| x.sin() + (10.0*x/3.0).sin();
+///
+/// let res = lipschitz_global(fun, 2.7, 7.5, 4.5, 1.0e-4, 0);
+///
+/// println!("xmin: {:.8} f(xmin): {:.8} lower bound: {:.8} evaluations: {}",
+///     res.xmin, res.fmin, res.lower_bound, res.nr_evaluations);
+///
+/// assert_float_absolute_eq!(res.xmin, 5.145735, 1.0e-4);
+/// assert!(res.lower_bound <= res.fmin && res.fmin - res.lower_bound <= 1.0e-4);
+/// ```
+pub fn lipschitz_global<F: Fn (f64) -> f64>(
+    fun: F,
+    a: f64,
+    b: f64,
+    lipschitz: f64,
+    tol: f64,
+    max_iterations: usize
+) -> LipschitzRes
+{
+    let tol = tol.max(MIN_TOLERANCE);
+    let max_iterations = if max_iterations < 2 { 1000 } else { max_iterations };
+    let adaptive = lipschitz <= 0.0;
+
+    let (a, b) = if a < b { (a, b) } else { (b, a) };
+
+    // Trial points sorted by abscissa.
+    let mut xs = vec![a, b];
+    let mut fs = vec![fun(a), fun(b)];
+
+    let (mut xmin, mut fmin) = if fs[0] <= fs[1] { (a, fs[0]) } else { (b, fs[1]) };
+    let mut l = lipschitz;
+    let mut lower_bound: f64;
+
+    loop {
+        if adaptive {
+            let slope = xs.windows(2).zip(fs.windows(2))
+                .map(|(x, f)| ((f[1] - f[0]) / (x[1] - x[0])).abs())
+                .fold(0.0, f64::max);
+            l = (ADAPTIVE_FACTOR * slope).max(MIN_LIPSCHITZ);
+        }
+
+        // Find the interval where the saw-tooth lower envelope is lowest.
+        let mut best = 0;
+        lower_bound = f64::INFINITY;
+        for i in 0..xs.len()-1 {
+            let r = 0.5 * (fs[i] + fs[i+1]) - 0.5 * l * (xs[i+1] - xs[i]);
+            if r < lower_bound {
+                lower_bound = r;
+                best = i;
+            }
+        }
+
+        if fmin - lower_bound <= tol || xs.len() >= max_iterations {
+            break;
+        }
+
+        // Intersection of the two cones from the ends of the interval.
+        let (x0, x1) = (xs[best], xs[best+1]);
+        let x = 0.5 * (x0 + x1) + 0.5 * (fs[best] - fs[best+1]) / l;
+        if x <= x0 || x >= x1 {
+            // The interval is resolved to machine precision.
+            break;
+        }
+
+        let fx = fun(x);
+        if fx < fmin {
+            xmin = x;
+            fmin = fx;
+        }
+        xs.insert(best+1, x);
+        fs.insert(best+1, fx);
+    }
+
+    LipschitzRes { xmin, fmin, lower_bound: lower_bound.min(fmin), lipschitz: l, nr_evaluations: xs.len() }
+}
+
+#[cfg(test)]
+#[test]
+fn test_sin_sin() {
+    let fun = |x: f64| x.sin() + (10.0*x/3.0).sin();
+
+    for l in [4.5, 10.0, 0.0] {
+        let res = lipschitz_global(fun, 2.7, 7.5, l, 1.0e-4, 0);
+
+        println!("xmin: {:.8} f(xmin): {:.8} lower bound: {:.8} L: {:.4} evaluations: {}",
+            res.xmin, res.fmin, res.lower_bound, res.lipschitz, res.nr_evaluations);
+
+        assert_float_absolute_eq!(res.xmin, 5.145735, 1.0e-3);
+        assert_float_absolute_eq!(res.fmin, -1.899599, 1.0e-4);
+        assert!(res.lower_bound <= -1.899599);
+        assert!(res.nr_evaluations < 1000);
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_cosine_vs_brent() {
+    use super::brent_search;
+
+    // Periodic signal, the global minimum -2 at x = 3*Pi/2 is deeper than the one at Pi/2.
+    let signal = |x: f64| (2.0*x).cos() + x.sin();
+
+    let (xmin_brent, fmin_brent, _) = brent_search(signal, 1.0, 1.2, 0.0, 0);
+    let res = lipschitz_global(signal, 0.0, 2.0*std::f64::consts::PI, 3.0, 1.0e-4, 0);
+
+    println!("xmin: {:.8} f(xmin): {:.8} vs brent {:.8} evaluations: {}",
+        res.xmin, res.fmin, xmin_brent, res.nr_evaluations);
+
+    assert_float_absolute_eq!(res.xmin, 1.5*std::f64::consts::PI, 1.0e-2);
+    assert_float_absolute_eq!(res.fmin, -2.0, 1.0e-4);
+    assert!(fmin_brent > -1.0);
+}
+
+#[cfg(test)]
+#[test]
+fn test_max_iterations() {
+    let fun = |x: f64| (5.0*x).sin();
+
+    let res = lipschitz_global(fun, -1.0, 1.0, 5.0, 0.0, 10);
+
+    assert_eq!(res.nr_evaluations, 10);
+    assert!(res.lower_bound <= -1.0);
+}
@@ -0,0 +1,400 @@
+//! Linear programming by the two-phase tableau simplex method.
+//!
+//! (c) 2023 Igor Lesik
+//! MIT license
+//!
+//! References:
+//!
+//! 1. William H. Press - Numerical recipes, the art of scientific computing.
+//!    Cambridge University Press (2007).
+//! 2. Robert G. Bland - New finite pivoting rules for the simplex method.
+//!    Mathematics of Operations Research 2 (1977).
+//! 3. Jorge Nocedal, Stephen J. Wright - Numerical Optimization. Springer (2006).
+//!
+use super::linalg::Matrix;
+
+/// Pivots smaller than this are treated as zero.
+const EPS: f64 = 1.0e-9_f64;
+
+/// Outcome of a linear program.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LpStatus {
+    /// Optimal solution found.
+    Optimal,
+    /// No point satisfies the constraints.
+    Infeasible,
+    /// The objective is unbounded from below on the feasible set.
+    Unbounded,
+    /// Iteration limit reached before optimality.
+    MaxIterations,
+}
+
+/// Result of a linear program.
+pub struct LpRes {
+    /// Outcome of the solve.
+    pub status: LpStatus,
+    /// Solution, meaningful when the status is `Optimal`.
+    pub x: Vec<f64>,
+    /// Objective value `c^T x`.
+    pub fun: f64,
+    /// Dual values (shadow prices) `d fun / d b_ub` of the inequality constraints, all `<= 0`.
+    pub duals_ub: Vec<f64>,
+    /// Dual values `d fun / d b_eq` of the equality constraints.
+    pub duals_eq: Vec<f64>,
+    /// Basic column of every constraint row. Column `j < n` is `x[j]`, column `n + i` is the
+    /// slack of the `i`-th inequality, larger columns are artificial variables left in the
+    /// basis by redundant equality constraints.
+    pub basis: Vec<usize>,
+    /// Number of simplex pivots in both phases.
+    pub nr_iterations: usize,
+}
+
+struct Tableau {
+    // Constraint rows followed by the objective row; the last column is the right-hand side.
+    t: Matrix,
+    m: usize,
+    basis: Vec<usize>,
+    // Columns that may enter the basis.
+    nr_allowed: usize,
+}
+
+impl Tableau {
+    fn rhs(&self) -> usize {
+        self.t.ncols - 1
+    }
+
+    fn pivot(&mut self, row: usize, col: usize) {
+        let ncols = self.t.ncols;
+        let p = self.t.get(row, col);
+        for j in 0..ncols {
+            let v = self.t.get(row, j) / p;
+            self.t.set(row, j, v);
+        }
+        for i in 0..=self.m {
+            let factor = self.t.get(i, col);
+            if i != row && factor != 0.0 {
+                for j in 0..ncols {
+                    let v = self.t.get(i, j) - factor * self.t.get(row, j);
+                    self.t.set(i, j, v);
+                }
+            }
+        }
+        self.basis[row] = col;
+    }
+
+    // Set the objective row to the reduced costs of `cost`.
+    fn set_objective(&mut self, cost: &[f64]) {
+        let m = self.m;
+        let rhs = self.rhs();
+        for j in 0..=rhs {
+            self.t.set(m, j, if j < cost.len() { cost[j] } else { 0.0 });
+        }
+        for i in 0..m {
+            let cb = cost[self.basis[i]];
+            if cb != 0.0 {
+                for j in 0..=rhs {
+                    let v = self.t.get(m, j) - cb * self.t.get(i, j);
+                    self.t.set(m, j, v);
+                }
+            }
+        }
+    }
+
+    // Simplex iterations with Bland's rule, which never cycles on degenerate vertices.
+    fn solve(&mut self, max_iterations: usize, nr_iterations: &mut usize) -> LpStatus {
+        let m = self.m;
+        let rhs = self.rhs();
+        loop {
+            // Entering column: the lowest index with a negative reduced cost.
+            let col = match (0..self.nr_allowed).find(|&j| self.t.get(m, j) < -EPS) {
+                Some(col) => col,
+                None => return LpStatus::Optimal,
+            };
+
+            if *nr_iterations >= max_iterations {
+                return LpStatus::MaxIterations;
+            }
+
+            // Leaving row: minimum ratio, ties broken by the lowest basic column.
+            let mut row = None;
+            let mut best_ratio = f64::INFINITY;
+            for i in 0..m {
+                let a = self.t.get(i, col);
+                if a > EPS {
+                    let ratio = self.t.get(i, rhs) / a;
+                    let better = match row {
+                        None => true,
+                        Some(r) => ratio < best_ratio - EPS
+                            || (ratio <= best_ratio + EPS && self.basis[i] < self.basis[r]),
+                    };
+                    if better {
+                        best_ratio = ratio;
+                        row = Some(i);
+                    }
+                }
+            }
+
+            match row {
+                Some(row) => self.pivot(row, col),
+                None => return LpStatus::Unbounded,
+            }
+            *nr_iterations += 1;
+        }
+    }
+}
+
+/// Linear programming by the two-phase simplex method.
+///
+/// - William H. Press - Numerical recipes, the art of scientific computing.
+///   Cambridge University Press (2007).
+/// - Robert G. Bland - New finite pivoting rules for the simplex method (1977).
+///
+/// Solves
+///
+/// ```text
+/// minimize c^T x  subject to  A_ub x <= b_ub,  A_eq x = b_eq,  x >= 0
+/// ```
+///
+/// where `a_ub` and `a_eq` are given by rows. Phase one minimizes the sum of artificial
+/// variables to find a feasible basis, phase two minimizes the objective from there.
+/// Pivots follow Bland's rule, so degenerate problems can't cycle.
+///
+/// At most `max_iterations` pivots are made (1000 if 0).
+///
+/// # Example
+///
+/// ```
+/// use rustamath_mnmz::{linprog, LpStatus};
+/// use assert_float_eq::*;
+/// // maximize 3x + 5y  subject to  x <= 4,  2y <= 12,  3x + 2y <= 18
+/// let res = linprog(&[-3.0, -5.0],
+///     &[vec![1.0, 0.0], vec![0.0, 2.0], vec![3.0, 2.0]], &[4.0, 12.0, 18.0],
+///     &[], &[], 0);
+///
+/// assert_eq!(res.status, LpStatus::Optimal);
+/// assert_float_absolute_eq!(res.x[0], 2.0, 1.0e-10);
+/// assert_float_absolute_eq!(res.x[1], 6.0, 1.0e-10);
+/// assert_float_absolute_eq!(res.fun, -36.0, 1.0e-10);
+/// assert_float_absolute_eq!(res.duals_ub[1], -1.5, 1.0e-10);
+/// ```
+pub fn linprog(
+    c: &[f64],
+    a_ub: &[Vec<f64>],
+    b_ub: &[f64],
+    a_eq: &[Vec<f64>],
+    b_eq: &[f64],
+    max_iterations: usize
+) -> LpRes
+{
+    let max_iterations = if max_iterations < 1 { 1000 } else { max_iterations };
+
+    let n = c.len();
+    let m_ub = b_ub.len();
+    let m_eq = b_eq.len();
+    let m = m_ub + m_eq;
+
+    // Rows with a negative right-hand side are negated. Inequalities that stay `<=` start
+    // with their slack in the basis, all other rows need an artificial variable.
+    let rows: Vec<(&[f64], f64)> = a_ub.iter().map(|r| r.as_slice()).zip(b_ub.iter().copied())
+        .chain(a_eq.iter().map(|r| r.as_slice()).zip(b_eq.iter().copied()))
+        .collect();
+    let sign: Vec<f64> = rows.iter().map(|r| if r.1 < 0.0 { -1.0 } else { 1.0 }).collect();
+    let needs_artificial: Vec<bool> = (0..m).map(|i| i >= m_ub || sign[i] < 0.0).collect();
+    let nr_artificial = needs_artificial.iter().filter(|&&a| a).count();
+
+    let nr_cols = n + m_ub + nr_artificial;
+    let mut tab = Tableau {
+        t: Matrix::new(m + 1, nr_cols + 1),
+        m,
+        basis: vec![0; m],
+        nr_allowed: nr_cols,
+    };
+
+    // Column holding the identity of every row, the dual values are read from it.
+    let mut identity_col = vec![0; m];
+    let mut next_artificial = n + m_ub;
+    for (i, (row, b)) in rows.iter().enumerate() {
+        for j in 0..n {
+            tab.t.set(i, j, sign[i] * row[j]);
+        }
+        if i < m_ub {
+            tab.t.set(i, n + i, sign[i]);
+        }
+        if needs_artificial[i] {
+            tab.t.set(i, next_artificial, 1.0);
+            identity_col[i] = next_artificial;
+            next_artificial += 1;
+        }
+        else {
+            identity_col[i] = n + i;
+        }
+        tab.t.set(i, nr_cols, sign[i] * b);
+        tab.basis[i] = identity_col[i];
+    }
+
+    let mut nr_iterations: usize = 0;
+
+    // Phase one: minimize the sum of the artificial variables.
+    if nr_artificial > 0 {
+        let mut cost = vec![0.0; nr_cols];
+        for c in cost.iter_mut().skip(n + m_ub) {
+            *c = 1.0;
+        }
+        tab.set_objective(&cost);
+        let status = tab.solve(max_iterations, &mut nr_iterations);
+        if status == LpStatus::MaxIterations {
+            return lp_failure(status, n, m_ub, m_eq, tab.basis, nr_iterations);
+        }
+        if -tab.t.get(m, nr_cols) > EPS * (1.0 + b_ub.iter().chain(b_eq.iter()).map(|b| b.abs()).sum::<f64>()) {
+            return lp_failure(LpStatus::Infeasible, n, m_ub, m_eq, tab.basis, nr_iterations);
+        }
+
+        // Drive the artificial variables out of the basis; those that can't leave
+        // belong to redundant equality rows and stay at zero.
+        for i in 0..m {
+            if tab.basis[i] >= n + m_ub {
+                if let Some(j) = (0..n + m_ub).find(|&j| tab.t.get(i, j).abs() > EPS) {
+                    tab.pivot(i, j);
+                }
+            }
+        }
+        tab.nr_allowed = n + m_ub;
+    }
+
+    // Phase two: minimize the objective.
+    let mut cost = c.to_vec();
+    cost.resize(nr_cols, 0.0);
+    tab.set_objective(&cost);
+    let status = tab.solve(max_iterations, &mut nr_iterations);
+    if status != LpStatus::Optimal {
+        return lp_failure(status, n, m_ub, m_eq, tab.basis, nr_iterations);
+    }
+
+    let mut x = vec![0.0; n];
+    for i in 0..m {
+        if tab.basis[i] < n {
+            x[tab.basis[i]] = tab.t.get(i, nr_cols);
+        }
+    }
+    let fun = c.iter().zip(x.iter()).map(|(ci, xi)| ci * xi).sum();
+
+    // Reduced cost of the identity column of row i is minus the dual of the scaled row.
+    let duals: Vec<f64> = (0..m).map(|i| -sign[i] * tab.t.get(m, identity_col[i])).collect();
+
+    LpRes {
+        status,
+        x,
+        fun,
+        duals_ub: duals[..m_ub].to_vec(),
+        duals_eq: duals[m_ub..].to_vec(),
+        basis: tab.basis,
+        nr_iterations,
+    }
+}
+
+fn lp_failure(
+    status: LpStatus,
+    n: usize,
+    m_ub: usize,
+    m_eq: usize,
+    basis: Vec<usize>,
+    nr_iterations: usize
+) -> LpRes
+{
+    LpRes {
+        status,
+        x: vec![0.0; n],
+        fun: if status == LpStatus::Unbounded { f64::NEG_INFINITY } else { f64::NAN },
+        duals_ub: vec![0.0; m_ub],
+        duals_eq: vec![0.0; m_eq],
+        basis,
+        nr_iterations,
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_product_mix() {
+    let a_ub = [vec![1.0, 0.0], vec![0.0, 2.0], vec![3.0, 2.0]];
+    let b_ub = [4.0, 12.0, 18.0];
+
+    let res = linprog(&[-3.0, -5.0], &a_ub, &b_ub, &[], &[], 0);
+
+    println!("x: {:?} fun: {} duals: {:?} basis: {:?} iterations: {}",
+        res.x, res.fun, res.duals_ub, res.basis, res.nr_iterations);
+
+    assert_eq!(res.status, LpStatus::Optimal);
+    assert_float_absolute_eq!(res.x[0], 2.0, 1.0e-10);
+    assert_float_absolute_eq!(res.x[1], 6.0, 1.0e-10);
+    assert_float_absolute_eq!(res.fun, -36.0, 1.0e-10);
+    assert_float_absolute_eq!(res.duals_ub[0], 0.0, 1.0e-10);
+    assert_float_absolute_eq!(res.duals_ub[1], -1.5, 1.0e-10);
+    assert_float_absolute_eq!(res.duals_ub[2], -1.0, 1.0e-10);
+
+    let mut basis = res.basis.clone();
+    basis.sort();
+    assert_eq!(basis, vec![0, 1, 2]); // x, y and the slack of x <= 4
+}
+
+#[cfg(test)]
+#[test]
+fn test_equality_and_duals() {
+    // minimize x + 2y + 3z  subject to  x + y >= 2,  y + z >= 1,  x + y + z = 4
+    let c = [1.0, 2.0, 3.0];
+    let a_ub = [vec![-1.0, -1.0, 0.0], vec![0.0, -1.0, -1.0]];
+    let a_eq = [vec![1.0, 1.0, 1.0]];
+
+    let res = linprog(&c, &a_ub, &[-2.0, -1.0], &a_eq, &[4.0], 0);
+
+    println!("x: {:?} fun: {} duals: {:?} {:?} iterations: {}",
+        res.x, res.fun, res.duals_ub, res.duals_eq, res.nr_iterations);
+
+    assert_eq!(res.status, LpStatus::Optimal);
+    assert_float_absolute_eq!(res.fun, 5.0, 1.0e-10);
+
+    // Dual values are the sensitivities of the optimum to the right-hand sides.
+    let h = 1.0e-3;
+    let res_ub = linprog(&c, &a_ub, &[-2.0, -1.0 + h], &a_eq, &[4.0], 0);
+    let res_eq = linprog(&c, &a_ub, &[-2.0, -1.0], &a_eq, &[4.0 + h], 0);
+    assert_float_absolute_eq!(res.duals_ub[1], (res_ub.fun - res.fun) / h, 1.0e-8);
+    assert_float_absolute_eq!(res.duals_eq[0], (res_eq.fun - res.fun) / h, 1.0e-8);
+    assert!(res.duals_ub.iter().all(|&y| y <= 0.0));
+}
+
+#[cfg(test)]
+#[test]
+fn test_infeasible_unbounded() {
+    // x <= 1 and x >= 2
+    let res = linprog(&[1.0], &[vec![1.0], vec![-1.0]], &[1.0, -2.0], &[], &[], 0);
+    assert_eq!(res.status, LpStatus::Infeasible);
+
+    // minimize -x  subject to  x - y <= 1
+    let res = linprog(&[-1.0, 0.0], &[vec![1.0, -1.0]], &[1.0], &[], &[], 0);
+    assert_eq!(res.status, LpStatus::Unbounded);
+
+    // Redundant equality rows keep an artificial variable in the basis.
+    let res = linprog(&[1.0, 1.0], &[], &[], &[vec![1.0, 1.0], vec![2.0, 2.0]], &[1.0, 2.0], 0);
+    assert_eq!(res.status, LpStatus::Optimal);
+    assert_float_absolute_eq!(res.fun, 1.0, 1.0e-10);
+}
+
+#[cfg(test)]
+#[test]
+fn test_beale_cycling() {
+    // Beale's example cycles with the textbook most-negative-cost rule.
+    let c = [-0.75, 20.0, -0.5, 6.0];
+    let a_ub = [
+        vec![0.25, -8.0, -1.0, 9.0],
+        vec![0.5, -12.0, -0.5, 3.0],
+        vec![0.0, 0.0, 1.0, 0.0],
+    ];
+
+    let res = linprog(&c, &a_ub, &[0.0, 0.0, 1.0], &[], &[], 0);
+
+    println!("x: {:?} fun: {} iterations: {}", res.x, res.fun, res.nr_iterations);
+
+    assert_eq!(res.status, LpStatus::Optimal);
+    assert_float_absolute_eq!(res.fun, -1.25, 1.0e-10);
+    assert_float_absolute_eq!(res.x[0], 1.0, 1.0e-10);
+    assert_float_absolute_eq!(res.x[2], 1.0, 1.0e-10);
+}
@@ -0,0 +1,327 @@
+//! Branch-and-bound for mixed-integer linear programs.
+//!
+//! (c) 2023 Igor Lesik
+//! MIT license
+//!
+//! References:
+//!
+//! 1. A. H. Land, A. G. Doig - An automatic method of solving discrete programming problems.
+//!    Econometrica 28 (1960).
+//! 2. Laurence A. Wolsey - Integer Programming. Wiley (1998).
+//!
+use super::lp::{linprog, LpStatus};
+
+/// Order in which open nodes of the search tree are explored.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NodeSelection {
+    /// Newest node first; finds incumbents quickly and keeps few nodes open.
+    DepthFirst,
+    /// Node with the lowest LP bound first; raises the best bound quickly.
+    BestBound,
+}
+
+/// Outcome of a mixed-integer linear program.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MilpStatus {
+    /// The incumbent is optimal within the gap tolerance.
+    Optimal,
+    /// No integer feasible point exists.
+    Infeasible,
+    /// The LP relaxation is unbounded.
+    Unbounded,
+    /// The node limit was reached; the incumbent, if any, may not be optimal.
+    NodeLimit,
+    /// The LP relaxation of some node hit the pivot limit; the incumbent, if any, may not be optimal.
+    LpFailure,
+}
+
+/// Branch-and-bound parameters.
+pub struct MilpOpts {
+    /// Node selection rule.
+    pub node_selection: NodeSelection,
+    /// Relative gap `(incumbent - bound) / max(1, |incumbent|)` under which the search stops.
+    pub gap_tol: f64,
+    /// Maximum number of LP relaxations solved.
+    pub node_limit: usize,
+    /// Distance from an integer under which a value counts as integral.
+    pub int_tol: f64,
+    /// Maximum number of simplex pivots per LP relaxation (the [`linprog`](crate::linprog) default if 0).
+    pub lp_max_iterations: usize,
+}
+
+impl Default for MilpOpts {
+    fn default() -> Self {
+        MilpOpts {
+            node_selection: NodeSelection::BestBound,
+            gap_tol: 1.0e-6,
+            node_limit: 10000,
+            int_tol: 1.0e-6,
+            lp_max_iterations: 0,
+        }
+    }
+}
+
+/// Result of branch-and-bound.
+pub struct MilpRes {
+    /// Outcome of the search.
+    pub status: MilpStatus,
+    /// Incumbent, the best integer feasible point found; empty if there is none.
+    pub x: Vec<f64>,
+    /// Objective value of the incumbent, infinity if there is none.
+    pub fun: f64,
+    /// Lower bound on the optimal objective value.
+    pub best_bound: f64,
+    /// Relative gap `(fun - best_bound) / max(1, |fun|)`.
+    pub gap: f64,
+    /// Number of nodes (LP relaxations) solved.
+    pub nr_nodes: usize,
+}
+
+struct Node {
+    // Branching bounds (variable, value, is upper bound) added to the relaxation.
+    bounds: Vec<(usize, f64, bool)>,
+    // LP bound of the parent.
+    bound: f64,
+}
+
+/// Branch-and-bound for mixed-integer linear programs.
+///
+/// - A. H. Land, A. G. Doig - An automatic method of solving discrete programming problems (1960).
+///
+/// Solves
+///
+/// ```text
+/// minimize c^T x  subject to  A_ub x <= b_ub,  A_eq x = b_eq,  x >= 0,  x[j] integer if integer[j]
+/// ```
+///
+/// Each node solves the LP relaxation with [`linprog`](crate::linprog) under the bounds of
+/// its branch. A node whose relaxation is integral gives a new incumbent; otherwise it is
+/// split on the most fractional integer variable `v` into the branches `x[j] <= floor(v)` and
+/// `x[j] >= ceil(v)`. Nodes whose bound is within the gap tolerance of the incumbent are
+/// pruned. The open nodes are explored in the order given by `opts.node_selection`.
+/// A node whose relaxation hits the pivot limit is not explored further; its parent bound
+/// is kept in `best_bound` and the status is [`MilpStatus::LpFailure`].
+///
+/// # Example
+///
+/// ```
+/// use rustamath_mnmz::{milp, MilpOpts, MilpStatus};
+/// use assert_float_eq::*;
+/// // 0-1 knapsack: maximize 8a + 11b + 6c + 4d  subject to  5a + 7b + 4c + 3d <= 14
+/// let mut a_ub = vec![vec![5.0, 7.0, 4.0, 3.0]];
+/// let mut b_ub = vec![14.0];
+/// for j in 0..4 {
+///     let mut row = vec![0.0; 4];
+///     row[j] = 1.0;
+///     a_ub.push(row);
+///     b_ub.push(1.0);
+/// }
+///
+/// let res = milp(&[-8.0, -11.0, -6.0, -4.0], &a_ub, &b_ub, &[], &[], &[true; 4], &MilpOpts::default());
+///
+/// assert_eq!(res.status, MilpStatus::Optimal);
+/// assert_float_absolute_eq!(res.fun, -21.0, 1.0e-9);
+/// assert_eq!(res.x, vec![0.0, 1.0, 1.0, 1.0]);
+/// ```
+pub fn milp(
+    c: &[f64],
+    a_ub: &[Vec<f64>],
+    b_ub: &[f64],
+    a_eq: &[Vec<f64>],
+    b_eq: &[f64],
+    integer: &[bool],
+    opts: &MilpOpts
+) -> MilpRes
+{
+    let n = c.len();
+    let tolerance = |fun: f64| opts.gap_tol * fun.abs().max(1.0);
+
+    let mut open = vec![Node { bounds: Vec::new(), bound: f64::NEG_INFINITY }];
+    let mut x: Vec<f64> = Vec::new();
+    let mut fun = f64::INFINITY;
+    // Lowest bound of the nodes pruned by the gap tolerance rather than by the incumbent.
+    let mut pruned_bound = f64::INFINITY;
+    // Lowest parent bound of the nodes whose relaxation hit the pivot limit.
+    let mut failed_bound = f64::INFINITY;
+    let mut status = MilpStatus::Optimal;
+    let mut nr_nodes: usize = 0;
+
+    loop {
+        let next = match opts.node_selection {
+            NodeSelection::DepthFirst => open.len().checked_sub(1),
+            NodeSelection::BestBound => (0..open.len()).min_by(|&i, &j| open[i].bound.total_cmp(&open[j].bound)),
+        };
+        let Some(next) = next else { break };
+        let node = open.remove(next);
+
+        if fun.is_finite() && node.bound >= fun - tolerance(fun) {
+            if node.bound < fun {
+                pruned_bound = pruned_bound.min(node.bound);
+            }
+            continue;
+        }
+        if nr_nodes >= opts.node_limit {
+            open.push(node);
+            status = MilpStatus::NodeLimit;
+            break;
+        }
+        nr_nodes += 1;
+
+        let mut rows = a_ub.to_vec();
+        let mut rhs = b_ub.to_vec();
+        for &(j, value, upper) in node.bounds.iter() {
+            let mut row = vec![0.0; n];
+            row[j] = if upper { 1.0 } else { -1.0 };
+            rows.push(row);
+            rhs.push(if upper { value } else { -value });
+        }
+        let lp = linprog(c, &rows, &rhs, a_eq, b_eq, opts.lp_max_iterations);
+        match lp.status {
+            LpStatus::Optimal => {}
+            LpStatus::Unbounded => {
+                status = MilpStatus::Unbounded;
+                break;
+            }
+            LpStatus::Infeasible => continue,
+            LpStatus::MaxIterations => {
+                failed_bound = failed_bound.min(node.bound);
+                continue;
+            }
+        }
+        if fun.is_finite() && lp.fun >= fun - tolerance(fun) {
+            if lp.fun < fun {
+                pruned_bound = pruned_bound.min(lp.fun);
+            }
+            continue;
+        }
+
+        // Most fractional integer variable.
+        let fractionality = |j: usize| {
+            let f = lp.x[j] - lp.x[j].floor();
+            f.min(1.0 - f)
+        };
+        let branch = (0..n)
+            .filter(|&j| integer[j] && fractionality(j) > opts.int_tol)
+            .max_by(|&i, &j| fractionality(i).total_cmp(&fractionality(j)));
+
+        match branch {
+            None => {
+                fun = lp.fun;
+                x = lp.x;
+                for j in (0..n).filter(|&j| integer[j]) {
+                    x[j] = x[j].round();
+                }
+            }
+            Some(j) => {
+                let value = lp.x[j];
+                let mut down = node.bounds.clone();
+                down.push((j, value.floor(), true));
+                let mut up = node.bounds;
+                up.push((j, value.ceil(), false));
+                // Depth-first dives into the branch nearer to the relaxed value.
+                let (first, second) = if value - value.floor() < 0.5 { (up, down) } else { (down, up) };
+                open.push(Node { bounds: first, bound: lp.fun });
+                open.push(Node { bounds: second, bound: lp.fun });
+            }
+        }
+    }
+
+    if status == MilpStatus::Optimal && failed_bound < f64::INFINITY {
+        status = MilpStatus::LpFailure;
+    }
+    if status == MilpStatus::Optimal && x.is_empty() {
+        status = MilpStatus::Infeasible;
+    }
+
+    let best_bound = if status == MilpStatus::Unbounded {
+        f64::NEG_INFINITY
+    }
+    else {
+        open.iter().map(|node| node.bound).fold(fun.min(pruned_bound).min(failed_bound), f64::min)
+    };
+    let gap = if x.is_empty() { f64::INFINITY } else { (fun - best_bound) / fun.abs().max(1.0) };
+
+    MilpRes { status, x, fun, best_bound, gap, nr_nodes }
+}
+
+#[cfg(test)]
+#[test]
+fn test_knapsack_node_selection() {
+    let mut a_ub = vec![vec![5.0, 7.0, 4.0, 3.0]];
+    let mut b_ub = vec![14.0];
+    for j in 0..4 {
+        let mut row = vec![0.0; 4];
+        row[j] = 1.0;
+        a_ub.push(row);
+        b_ub.push(1.0);
+    }
+    let c = [-8.0, -11.0, -6.0, -4.0];
+
+    for selection in [NodeSelection::DepthFirst, NodeSelection::BestBound] {
+        let opts = MilpOpts { node_selection: selection, ..Default::default() };
+        let res = milp(&c, &a_ub, &b_ub, &[], &[], &[true; 4], &opts);
+
+        println!("{:?}: x: {:?} fun: {} bound: {} gap: {} nodes: {}",
+            selection, res.x, res.fun, res.best_bound, res.gap, res.nr_nodes);
+
+        assert_eq!(res.status, MilpStatus::Optimal);
+        assert_float_absolute_eq!(res.fun, -21.0, 1.0e-9);
+        assert_eq!(res.x, vec![0.0, 1.0, 1.0, 1.0]);
+        assert!(res.gap <= 1.0e-6);
+    }
+
+    // With a single node only the LP relaxation bound is known.
+    let opts = MilpOpts { node_limit: 1, ..Default::default() };
+    let res = milp(&c, &a_ub, &b_ub, &[], &[], &[true; 4], &opts);
+    assert_eq!(res.status, MilpStatus::NodeLimit);
+    assert_eq!(res.nr_nodes, 1);
+    assert_float_absolute_eq!(res.best_bound, -22.0, 1.0e-9);
+}
+
+#[cfg(test)]
+#[test]
+fn test_mixed_and_infeasible() {
+    // maximize y  subject to  -x + y <= 1,  3x + 2y <= 12,  2x + 3y <= 12, LP optimum y = 2.8.
+    let a_ub = [vec![-1.0, 1.0], vec![3.0, 2.0], vec![2.0, 3.0]];
+    let b_ub = [1.0, 12.0, 12.0];
+
+    let res = milp(&[0.0, -1.0], &a_ub, &b_ub, &[], &[], &[true, true], &MilpOpts::default());
+    println!("x: {:?} fun: {} nodes: {}", res.x, res.fun, res.nr_nodes);
+    assert_eq!(res.status, MilpStatus::Optimal);
+    assert_float_absolute_eq!(res.fun, -2.0, 1.0e-9);
+    assert_eq!(res.x[0].fract(), 0.0);
+
+    // Only x integer: y stays continuous.
+    let res = milp(&[0.0, -1.0], &a_ub, &b_ub, &[], &[], &[true, false], &MilpOpts::default());
+    println!("x: {:?} fun: {} nodes: {}", res.x, res.fun, res.nr_nodes);
+    assert_eq!(res.status, MilpStatus::Optimal);
+    assert_float_absolute_eq!(res.fun, -8.0 / 3.0, 1.0e-9);
+
+    // 2x = 1 has no integer solution.
+    let res = milp(&[1.0], &[], &[], &[vec![2.0]], &[1.0], &[true], &MilpOpts::default());
+    assert_eq!(res.status, MilpStatus::Infeasible);
+    assert!(res.x.is_empty());
+}
+
+#[cfg(test)]
+#[test]
+fn test_lp_failure() {
+    let mut a_ub = vec![vec![5.0, 7.0, 4.0, 3.0]];
+    let mut b_ub = vec![14.0];
+    for j in 0..4 {
+        let mut row = vec![0.0; 4];
+        row[j] = 1.0;
+        a_ub.push(row);
+        b_ub.push(1.0);
+    }
+    let c = [-8.0, -11.0, -6.0, -4.0];
+
+    // One pivot is not enough to solve the root relaxation.
+    let opts = MilpOpts { lp_max_iterations: 1, ..Default::default() };
+    let res = milp(&c, &a_ub, &b_ub, &[], &[], &[true; 4], &opts);
+    println!("x: {:?} fun: {} bound: {} nodes: {}", res.x, res.fun, res.best_bound, res.nr_nodes);
+    assert_eq!(res.status, MilpStatus::LpFailure);
+    assert!(res.x.is_empty());
+    assert_eq!(res.best_bound, f64::NEG_INFINITY);
+    assert_eq!(res.gap, f64::INFINITY);
+}
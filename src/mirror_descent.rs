@@ -0,0 +1,235 @@
+//! Minimization over the probability simplex by mirror descent.
+//!
+//! (c) 2023 Igor Lesik
+//! MIT license
+//!
+//! References:
+//!
+//! 1. Amir Beck, Marc Teboulle - Mirror descent and nonlinear projected subgradient methods
+//!    for convex optimization. Operations Research Letters 31 (2003).
+//! 2. Jyrki Kivinen, Manfred K. Warmuth - Exponentiated gradient versus gradient descent
+//!    for linear predictors. Information and Computation 132 (1997).
+//! 3. John Duchi, Shai Shalev-Shwartz, Yoram Singer, Tushar Chandra - Efficient projections
+//!    onto the l1-ball for learning in high dimensions. ICML (2008).
+//!
+
+/// Smallest allowed duality gap tolerance.
+const MIN_TOLERANCE: f64 = 1.0e-14_f64;
+
+/// Result of minimization over the simplex.
+pub struct SimplexRes {
+    /// Location of the minimum, non-negative weights summing to one.
+    pub xmin: Vec<f64>,
+    /// Function value at `xmin`.
+    pub fmin: f64,
+    /// Frank-Wolfe duality gap `grad^T x - min(grad)` at `xmin`, an upper bound on
+    /// `fmin - f*` for convex `f`.
+    pub gap: f64,
+    /// Whether the gap fell below the tolerance.
+    pub converged: bool,
+    /// Number of iterations.
+    pub nr_iterations: usize,
+    /// Number of function and gradient evaluations.
+    pub nr_evaluations: usize,
+}
+
+/// Euclidean projection of `v` onto the probability simplex `x >= 0, sum(x) = 1`.
+///
+/// - John Duchi et al. - Efficient projections onto the l1-ball for learning in high
+///   dimensions (2008).
+///
+/// The projection is `max(v_i - theta, 0)`, with the threshold `theta` found by sorting.
+///
+/// # Example
+///
+/// ```
+/// use rustamath_mnmz::project_onto_simplex;
+/// let x = project_onto_simplex(&[0.6, 0.6, -1.0]);
+/// assert_eq!(x, vec![0.5, 0.5, 0.0]);
+/// ```
+pub fn project_onto_simplex(v: &[f64]) -> Vec<f64> {
+    let mut u = v.to_vec();
+    u.sort_by(|a, b| b.total_cmp(a));
+    let mut sum = 0.0;
+    let mut theta = 0.0;
+    for (k, uk) in u.iter().enumerate() {
+        sum += uk;
+        let t = (sum - 1.0) / (k + 1) as f64;
+        if uk - t > 0.0 {
+            theta = t;
+        }
+    }
+    v.iter().map(|vi| (vi - theta).max(0.0)).collect()
+}
+
+/// Exponentiated gradient (entropic mirror descent) over the probability simplex.
+///
+/// - Amir Beck, Marc Teboulle - Mirror descent and nonlinear projected subgradient methods
+///   for convex optimization (2003).
+///
+/// Minimizes `f(x)` over `x >= 0, sum(x) = 1`. The function `fun(x, g)` returns `f(x)` and
+/// stores the gradient in `g`.
+///
+/// Each iteration takes the multiplicative step `x_i <- x_i exp(-eta g_i) / Z`, which stays
+/// inside the simplex without any projection. The step `eta` is found by backtracking until
+/// `f(x+) <= f(x) + g^T (x+ - x) + KL(x+, x) / eta`, and is enlarged after every accepted
+/// step. The Frank-Wolfe duality gap `g^T x - min(g)` bounds the distance to the minimum
+/// of a convex `f` and serves as the stopping test against `tol`. Near the minimum `f`
+/// changes by about the square of the gap, so gaps much below `sqrt(f64::EPSILON) |f|` are
+/// out of reach for functions with curvature in the interior of the simplex.
+///
+/// Components that are zero stay zero, so `point` is projected onto the simplex and moved
+/// slightly toward its center if it has zeros. At most `max_iterations` iterations are
+/// made (1000 if 0).
+///
+/// # Example
+///
+/// ```
+/// use rustamath_mnmz::exponentiated_gradient;
+/// use assert_float_eq::*;
+/// // Distance to a point outside of the simplex.
+/// let c = [0.8, 0.5, -0.1];
+/// let fun = |x: &[f64], g: &mut [f64]| {
+///     let mut f = 0.0;
+///     for i in 0..3 {
+///         g[i] = 2.0 * (x[i] - c[i]);
+///         f += (x[i] - c[i]).powi(2);
+///     }
+///     f
+/// };
+///
+/// let res = exponentiated_gradient(fun, &[1.0, 1.0, 1.0], 1.0e-8, 0);
+///
+/// assert!(res.converged);
+/// assert_float_absolute_eq!(res.xmin[0], 0.65, 1.0e-4);
+/// assert_float_absolute_eq!(res.xmin[1], 0.35, 1.0e-4);
+/// ```
+pub fn exponentiated_gradient<F: Fn (&[f64], &mut [f64]) -> f64>(
+    fun: F,
+    point: &[f64],
+    tol: f64,
+    max_iterations: usize
+) -> SimplexRes
+{
+    let tol = tol.max(MIN_TOLERANCE);
+    let max_iterations = if max_iterations < 1 { 1000 } else { max_iterations };
+    let n = point.len();
+
+    let dot = |a: &[f64], b: &[f64]| a.iter().zip(b.iter()).map(|(x, y)| x * y).sum::<f64>();
+    let gap = |x: &[f64], g: &[f64]| dot(g, x) - g.iter().fold(f64::INFINITY, |m, &gi| m.min(gi));
+
+    let mut x = project_onto_simplex(point);
+    if x.iter().any(|&xi| xi <= 0.0) {
+        x.iter_mut().for_each(|xi| *xi = 0.999 * *xi + 0.001 / n as f64);
+    }
+    let mut g = vec![0.0; n];
+    let mut fx = fun(&x, &mut g);
+    let mut nr_evaluations: usize = 1;
+
+    let mut xnew = vec![0.0; n];
+    let mut gnew = vec![0.0; n];
+    let mut eta = 1.0 / g.iter().fold(f64::MIN_POSITIVE, |m, gi| m.max(gi.abs()));
+    let mut converged = false;
+    let mut nr_iterations: usize = 0;
+
+    while nr_iterations < max_iterations {
+        if gap(&x, &g) <= tol {
+            converged = true;
+            break;
+        }
+        nr_iterations += 1;
+
+        let gmin = g.iter().fold(f64::INFINITY, |m, &gi| m.min(gi));
+        loop {
+            // Multiplicative update, shifted by min(g) to avoid overflow.
+            for i in 0..n {
+                xnew[i] = x[i] * (-eta * (g[i] - gmin)).exp();
+            }
+            let z: f64 = xnew.iter().sum();
+            xnew.iter_mut().for_each(|xi| *xi /= z);
+
+            let fnew = fun(&xnew, &mut gnew);
+            nr_evaluations += 1;
+            let kl: f64 = (0..n).filter(|&i| xnew[i] > 0.0).map(|i| xnew[i] * (xnew[i] / x[i]).ln()).sum();
+            let linear: f64 = (0..n).map(|i| g[i] * (xnew[i] - x[i])).sum();
+            // Rounding error of f is tolerated, it dominates the test near the minimum.
+            if fnew <= fx + linear + kl / eta + 4.0 * f64::EPSILON * fx.abs() || eta < f64::EPSILON {
+                fx = fnew;
+                break;
+            }
+            eta *= 0.5;
+        }
+        std::mem::swap(&mut x, &mut xnew);
+        std::mem::swap(&mut g, &mut gnew);
+        eta *= 1.5;
+    }
+
+    SimplexRes {
+        gap: gap(&x, &g),
+        xmin: x,
+        fmin: fx,
+        converged,
+        nr_iterations,
+        nr_evaluations,
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_projection() {
+    assert_eq!(project_onto_simplex(&[2.0, 0.0, 0.0]), vec![1.0, 0.0, 0.0]);
+    assert_eq!(project_onto_simplex(&[0.2, 0.3, 0.5]), vec![0.2, 0.3, 0.5]);
+    let x = project_onto_simplex(&[0.5, 0.5, 0.5]);
+    for xi in x.iter() {
+        assert_float_absolute_eq!(*xi, 1.0 / 3.0, 1.0e-15);
+    }
+    let x = project_onto_simplex(&[-3.0, 1.5, 0.7, 1.1]);
+    println!("{:?}", x);
+    assert_float_absolute_eq!(x.iter().sum::<f64>(), 1.0, 1.0e-15);
+    assert_eq!(x[0], 0.0);
+    assert_float_absolute_eq!(x[1] - x[3], 0.4, 1.0e-15);
+}
+
+#[cfg(test)]
+#[test]
+fn test_entropy_and_projection() {
+    // Negative entropy plus a linear term, minimum at softmax(-a).
+    let a = [0.3, -1.2, 2.0, 0.0, 0.7];
+    let fun = |x: &[f64], g: &mut [f64]| {
+        let mut f = 0.0;
+        for i in 0..x.len() {
+            g[i] = x[i].ln() + 1.0 + a[i];
+            f += x[i] * x[i].ln() + a[i] * x[i];
+        }
+        f
+    };
+    let res = exponentiated_gradient(fun, &[0.2; 5], 1.0e-8, 0);
+    println!("x: {:?} gap: {} iterations: {} evaluations: {}",
+        res.xmin, res.gap, res.nr_iterations, res.nr_evaluations);
+
+    let z: f64 = a.iter().map(|ai| (-ai).exp()).sum();
+    assert!(res.converged);
+    for (xi, ai) in res.xmin.iter().zip(a.iter()) {
+        assert_float_absolute_eq!(*xi, (-ai).exp() / z, 1.0e-6);
+    }
+
+    // Squared distance: the minimizer over the simplex is the Euclidean projection.
+    let c = [0.9, -0.4, 0.6, 0.1];
+    let fun = |x: &[f64], g: &mut [f64]| {
+        let mut f = 0.0;
+        for i in 0..x.len() {
+            g[i] = 2.0 * (x[i] - c[i]);
+            f += (x[i] - c[i]).powi(2);
+        }
+        f
+    };
+    let res = exponentiated_gradient(fun, &[0.25; 4], 1.0e-10, 0);
+    println!("x: {:?} gap: {} iterations: {} evaluations: {}",
+        res.xmin, res.gap, res.nr_iterations, res.nr_evaluations);
+
+    assert!(res.converged);
+    assert!(res.gap <= 1.0e-10);
+    for (xi, pi) in res.xmin.iter().zip(project_onto_simplex(&c).iter()) {
+        assert_float_absolute_eq!(*xi, *pi, 1.0e-5);
+    }
+}
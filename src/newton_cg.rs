@@ -0,0 +1,300 @@
+//! Truncated Newton minimization with conjugate gradients (Newton-CG).
+//!
+//! (c) 2023 Igor Lesik
+//! MIT license
+//!
+//! References:
+//!
+//! 1. Jorge Nocedal, Stephen J. Wright - Numerical Optimization.
+//!    Springer (2006). Section 7.1.
+//! 2. Stephen G. Nash - A survey of truncated-Newton methods.
+//!    Journal of Computational and Applied Mathematics 124 (2000).
+//!
+
+/// Smallest allowed gradient tolerance.
+const MIN_TOLERANCE: f64 = 1.0e-14_f64;
+
+/// Sufficient decrease parameter of the Armijo line search.
+const ARMIJO: f64 = 1.0e-4_f64;
+
+/// Result of Newton-CG.
+pub struct NewtonCgRes {
+    /// Location of the minimum.
+    pub xmin: Vec<f64>,
+    /// Function value at `xmin`.
+    pub fmin: f64,
+    /// Infinity norm of the gradient at `xmin`.
+    pub grad_norm: f64,
+    /// Whether the gradient norm fell below the tolerance.
+    pub converged: bool,
+    /// Number of Newton iterations.
+    pub nr_iterations: usize,
+    /// Number of function and gradient evaluations.
+    pub nr_evaluations: usize,
+    /// Number of Hessian-vector products.
+    pub nr_hessp: usize,
+}
+
+/// Newton-CG minimization with Hessian-vector products.
+///
+/// - Jorge Nocedal, Stephen J. Wright - Numerical Optimization (2006), Algorithm 7.1.
+///
+/// The function `fun(x, g)` returns `f(x)` and stores the gradient in `g`, and
+/// `hessp(x, v, out)` stores the product of the Hessian at `x` with `v` in `out`. The
+/// Hessian itself is never formed; [`with_hessp`] builds `hessp` from the gradient alone.
+///
+/// Each iteration solves the Newton equations `H p = -g` approximately by conjugate
+/// gradients, stopping when the residual is below `min(0.5, sqrt(|g|)) |g|`, which gives
+/// superlinear convergence near the minimum. When CG meets a direction of non-positive
+/// curvature the Hessian is not positive definite and CG exits with the step built so
+/// far, or with the steepest descent direction on the first CG iteration. The step is
+/// taken with a backtracking Armijo line search.
+///
+/// Stops when the infinity norm of the gradient is below `tol`, or after `max_iterations`
+/// iterations (1000 if 0).
+///
+/// # Example
+///
+/// ```
+/// use rustamath_mnmz::newton_cg;
+/// use assert_float_eq::*;
+/// let rosenbrock = |x: &[f64], g: &mut [f64]| {
+///     g[0] = -2.0*(1.0 - x[0]) - 400.0*x[0]*(x[1] - x[0]*x[0]);
+///     g[1] = 200.0*(x[1] - x[0]*x[0]);
+///     (1.0 - x[0]).powi(2) + 100.0*(x[1] - x[0]*x[0]).powi(2)
+/// };
+/// let hessp = |x: &[f64], v: &[f64], out: &mut [f64]| {
+///     out[0] = (1200.0*x[0]*x[0] - 400.0*x[1] + 2.0)*v[0] - 400.0*x[0]*v[1];
+///     out[1] = -400.0*x[0]*v[0] + 200.0*v[1];
+/// };
+///
+/// let res = newton_cg(rosenbrock, hessp, &[-1.2, 1.0], 1.0e-10, 0);
+///
+/// assert!(res.converged);
+/// assert_float_absolute_eq!(res.xmin[0], 1.0, 1.0e-10);
+/// assert_float_absolute_eq!(res.xmin[1], 1.0, 1.0e-10);
+/// ```
+pub fn newton_cg<F, H>(
+    fun: F,
+    hessp: H,
+    point: &[f64],
+    tol: f64,
+    max_iterations: usize
+) -> NewtonCgRes
+where
+    F: Fn (&[f64], &mut [f64]) -> f64,
+    H: Fn (&[f64], &[f64], &mut [f64])
+{
+    let tol = tol.max(MIN_TOLERANCE);
+    let max_iterations = if max_iterations < 1 { 1000 } else { max_iterations };
+    let n = point.len();
+
+    let dot = |a: &[f64], b: &[f64]| a.iter().zip(b.iter()).map(|(x, y)| x * y).sum::<f64>();
+    let inf_norm = |v: &[f64]| v.iter().fold(0.0_f64, |s, vi| s.max(vi.abs()));
+
+    let mut x = point.to_vec();
+    let mut g = vec![0.0; n];
+    let mut fx = fun(&x, &mut g);
+    let mut nr_evaluations: usize = 1;
+    let mut nr_hessp: usize = 0;
+    let mut converged = false;
+    let mut nr_iterations: usize = 0;
+
+    let mut p = vec![0.0; n];
+    let mut r = vec![0.0; n];
+    let mut d = vec![0.0; n];
+    let mut hd = vec![0.0; n];
+    let mut xnew = vec![0.0; n];
+    let mut gnew = vec![0.0; n];
+
+    while nr_iterations < max_iterations {
+        if inf_norm(&g) <= tol {
+            converged = true;
+            break;
+        }
+        nr_iterations += 1;
+
+        // Conjugate gradients on H p = -g, residual r = H p + g.
+        let gnorm = dot(&g, &g).sqrt();
+        let forcing = gnorm.sqrt().min(0.5) * gnorm;
+        p.iter_mut().for_each(|pi| *pi = 0.0);
+        r.copy_from_slice(&g);
+        d.iter_mut().zip(g.iter()).for_each(|(di, gi)| *di = -gi);
+        let mut rr = gnorm * gnorm;
+        for j in 0..2 * n.max(1) {
+            hessp(&x, &d, &mut hd);
+            nr_hessp += 1;
+            let curvature = dot(&d, &hd);
+            if curvature <= f64::EPSILON * dot(&d, &d) {
+                // Negative curvature, use the step so far or steepest descent.
+                if j == 0 {
+                    p.copy_from_slice(&d);
+                }
+                break;
+            }
+            let alpha = rr / curvature;
+            for i in 0..n {
+                p[i] += alpha * d[i];
+                r[i] += alpha * hd[i];
+            }
+            let rr_new = dot(&r, &r);
+            if rr_new.sqrt() <= forcing {
+                break;
+            }
+            let beta = rr_new / rr;
+            rr = rr_new;
+            d.iter_mut().zip(r.iter()).for_each(|(di, ri)| *di = -ri + beta * *di);
+        }
+
+        // Backtracking line search, the steepest descent step is scaled to unit length.
+        let slope = dot(&g, &p);
+        if slope >= 0.0 {
+            break;
+        }
+        let mut step = if p.iter().zip(g.iter()).all(|(pi, gi)| *pi == -gi) {
+            (1.0 / inf_norm(&p)).min(1.0)
+        }
+        else {
+            1.0
+        };
+        let mut accepted = false;
+        for _ in 0..60 {
+            for i in 0..n {
+                xnew[i] = x[i] + step * p[i];
+            }
+            let fnew = fun(&xnew, &mut gnew);
+            nr_evaluations += 1;
+            if fnew <= fx + ARMIJO * step * slope {
+                accepted = true;
+                fx = fnew;
+                break;
+            }
+            step *= 0.5;
+        }
+        if !accepted {
+            break;
+        }
+        std::mem::swap(&mut x, &mut xnew);
+        std::mem::swap(&mut g, &mut gnew);
+    }
+
+    NewtonCgRes {
+        grad_norm: inf_norm(&g),
+        xmin: x,
+        fmin: fx,
+        converged,
+        nr_iterations,
+        nr_evaluations,
+        nr_hessp,
+    }
+}
+
+/// Turn a function in the `fun(x, g) -> f(x)` form into the `hessp(x, v, out)` form of
+/// [`newton_cg`], with Hessian-vector products by central differences of the gradient.
+///
+/// `H v = (g(x + h v) - g(x - h v)) / 2h` with `h = f64::EPSILON^(1/3) max(|x|, 1) / |v|`
+/// costs two gradient evaluations.
+///
+/// # Example
+///
+/// ```
+/// use rustamath_mnmz::{newton_cg, with_hessp};
+/// use assert_float_eq::*;
+/// let rosenbrock = |x: &[f64], g: &mut [f64]| {
+///     g[0] = -2.0*(1.0 - x[0]) - 400.0*x[0]*(x[1] - x[0]*x[0]);
+///     g[1] = 200.0*(x[1] - x[0]*x[0]);
+///     (1.0 - x[0]).powi(2) + 100.0*(x[1] - x[0]*x[0]).powi(2)
+/// };
+///
+/// let res = newton_cg(rosenbrock, with_hessp(rosenbrock), &[-1.2, 1.0], 1.0e-8, 0);
+///
+/// assert!(res.converged);
+/// assert_float_absolute_eq!(res.xmin[0], 1.0, 1.0e-8);
+/// ```
+pub fn with_hessp<F: Fn (&[f64], &mut [f64]) -> f64>(fun: F) -> impl Fn (&[f64], &[f64], &mut [f64]) {
+    move |x, v, out| {
+        let n = x.len();
+        let xnorm = x.iter().map(|xi| xi * xi).sum::<f64>().sqrt();
+        let vnorm = v.iter().map(|vi| vi * vi).sum::<f64>().sqrt();
+        if vnorm == 0.0 {
+            out.iter_mut().for_each(|o| *o = 0.0);
+            return;
+        }
+        let h = f64::EPSILON.cbrt() * xnorm.max(1.0) / vnorm;
+        let mut xt: Vec<f64> = (0..n).map(|i| x[i] + h * v[i]).collect();
+        let mut gm = vec![0.0; n];
+        fun(&xt, out);
+        for i in 0..n {
+            xt[i] = x[i] - h * v[i];
+        }
+        fun(&xt, &mut gm);
+        for i in 0..n {
+            out[i] = (out[i] - gm[i]) / (2.0 * h);
+        }
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_extended_rosenbrock() {
+    // Extended Rosenbrock function, block diagonal Hessian.
+    let fun = |x: &[f64], g: &mut [f64]| {
+        let mut f = 0.0;
+        for i in (0..x.len()).step_by(2) {
+            let t1 = 1.0 - x[i];
+            let t2 = x[i + 1] - x[i] * x[i];
+            g[i] = -2.0 * t1 - 400.0 * x[i] * t2;
+            g[i + 1] = 200.0 * t2;
+            f += t1 * t1 + 100.0 * t2 * t2;
+        }
+        f
+    };
+    let hessp = |x: &[f64], v: &[f64], out: &mut [f64]| {
+        for i in (0..x.len()).step_by(2) {
+            out[i] = (1200.0 * x[i] * x[i] - 400.0 * x[i + 1] + 2.0) * v[i] - 400.0 * x[i] * v[i + 1];
+            out[i + 1] = -400.0 * x[i] * v[i] + 200.0 * v[i + 1];
+        }
+    };
+    let n = 1000;
+    let point: Vec<f64> = (0..n).map(|i| if i % 2 == 0 { -1.2 } else { 1.0 }).collect();
+
+    let exact = newton_cg(fun, hessp, &point, 1.0e-9, 0);
+    println!("exact: f: {:e} iterations: {} evaluations: {} hessp: {}",
+        exact.fmin, exact.nr_iterations, exact.nr_evaluations, exact.nr_hessp);
+    let fd = newton_cg(fun, with_hessp(fun), &point, 1.0e-9, 0);
+    println!("differences: f: {:e} iterations: {} evaluations: {} hessp: {}",
+        fd.fmin, fd.nr_iterations, fd.nr_evaluations, fd.nr_hessp);
+
+    for res in [exact, fd] {
+        assert!(res.converged);
+        assert!(res.grad_norm <= 1.0e-9);
+        assert!(res.nr_iterations < 100);
+        for xi in res.xmin.iter() {
+            assert_float_absolute_eq!(*xi, 1.0, 1.0e-9);
+        }
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_negative_curvature() {
+    // Double well in x, started on the hump where the Hessian is indefinite.
+    let fun = |x: &[f64], g: &mut [f64]| {
+        g[0] = 4.0 * x[0].powi(3) - 2.0 * x[0];
+        g[1] = 2.0 * x[1];
+        x[0].powi(4) - x[0] * x[0] + x[1] * x[1]
+    };
+    let hessp = |x: &[f64], v: &[f64], out: &mut [f64]| {
+        out[0] = (12.0 * x[0] * x[0] - 2.0) * v[0];
+        out[1] = 2.0 * v[1];
+    };
+
+    let res = newton_cg(fun, hessp, &[0.05, 1.0], 1.0e-12, 0);
+    println!("x: {:?} f: {} iterations: {}", res.xmin, res.fmin, res.nr_iterations);
+
+    // Pure Newton would head for the saddle at x = 0.
+    assert!(res.converged);
+    assert_float_absolute_eq!(res.xmin[0], 0.5_f64.sqrt(), 1.0e-12);
+    assert_float_absolute_eq!(res.xmin[1], 0.0, 1.0e-12);
+    assert_float_absolute_eq!(res.fmin, -0.25, 1.0e-15);
+}
@@ -0,0 +1,155 @@
+//! Safeguarded Newton's method with second derivative for searching for a minimum.
+//!
+//! (c) 2023 Igor Lesik
+//! MIT license
+//!
+//! References:
+//!
+//! 1. William H. Press - Numerical recipes, the art of scientific computing.
+//!    Cambridge University Press (2007). Section 9.4.
+//!
+use super::bracket::find_bracket;
+
+/// Smallest tolerance.
+///
+/// Newton's method finds the root of `f'`, so unlike the methods using only `f`
+/// the minimum can be located to nearly f64 precision.
+const MIN_TOLERANCE: f64 = 1.0e-14_f64;
+
+/// Safeguarded Newton's method to search for a minimum.
+///
+/// - William H. Press - Numerical recipes, the art of scientific computing.
+///   Cambridge University Press (2007).
+///
+/// The function `fun(x)` returns `(f(x), f'(x), f''(x))`. A minimum is first bracketed by
+/// [`find_bracket`](crate::find_bracket) starting from `a` and `b`. Every iteration
+/// shrinks the bracket to the side where `f'` points downhill and takes the Newton step
+/// `-f'/f''`, which converges quadratically near the minimum. As in `rtsafe` the step falls
+/// back to bisection of the bracket when the curvature is not positive or the Newton
+/// step would leave the bracket.
+///
+/// Returns the location of the minimum, the function value there and the number of
+/// iterations.
+///
+/// # Example
+///
+/// ```
+/// use rustamath_mnmz::{newton_search, brent_df_search};
+/// use assert_float_eq::*;
+/// let cosine = |x: f64| (x.cos(), -x.sin(), -x.cos()); // Minimum at Pi when x ∈ [0, 2*Pi].
+///
+/// let (xmin, f, nr_iterations) = newton_search(cosine, 0.01, 1.0, 0.0, 0);
+/// let (_, _, nr_iterations_df) = brent_df_search(|x| (cosine(x).0, cosine(x).1), 0.01, 1.0, 0.0, 0);
+///
+/// println!("xmin: {:.8} f(xmin): {:6.2} iterations: {} vs brent_df {}",
+///     xmin, f, nr_iterations, nr_iterations_df);
+///
+/// assert_float_relative_eq!(xmin, std::f64::consts::PI, 1.0e-14);
+/// ```
+pub fn newton_search<F: Fn (f64) -> (f64, f64, f64)>(
+    fun: F,
+    a: f64,
+    b: f64,
+    tol: f64,
+    max_iterations: usize
+) -> (f64, f64, usize)
+{
+    let tol = tol.max(MIN_TOLERANCE);
+    let max_iterations = if max_iterations < 1 { 500 } else { max_iterations.min(1000) };
+
+    // Protects against trying to achieve fractional accuracy for a minimum at zero.
+    const ZEPS: f64 = f64::EPSILON * 1.0e-3;
+
+    let bracket = find_bracket(|x| fun(x).0, a, b);
+    let mut lo = bracket.a.min(bracket.c);
+    let mut hi = bracket.a.max(bracket.c);
+
+    let mut x = bracket.b;
+    let (mut fx, mut dx, mut d2x) = fun(x);
+
+    let mut nr_iterations: usize = 0;
+
+    while nr_iterations < max_iterations {
+        // The minimum is on the downhill side of x.
+        if dx > 0.0 || (dx == 0.0 && d2x < 0.0) {
+            hi = x;
+        }
+        else if dx < 0.0 {
+            lo = x;
+        }
+        else {
+            break;
+        }
+
+        let tol1 = tol * x.abs() + ZEPS;
+        let step = -dx / d2x;
+        // Converged, the Newton correction is below the tolerance.
+        if d2x > 0.0 && step.abs() <= tol1 {
+            break;
+        }
+        let u = if d2x > 0.0 && lo < x + step && x + step < hi {
+            x + step
+        }
+        else {
+            0.5 * (lo + hi)
+        };
+
+        let done = hi - lo <= 2.0 * tol1;
+        x = u;
+        (fx, dx, d2x) = fun(x);
+        nr_iterations += 1;
+
+        if done {
+            break;
+        }
+    }
+
+    (x, fx, nr_iterations)
+}
+
+#[cfg(test)]
+#[test]
+fn test_poly2_cosine() {
+    use super::brent_df_search;
+
+    // Roots 1.0 and 2.0, minimum at 1.5, found by the first Newton step.
+    let poly2 = |x: f64| ((x-1.0)*(x-2.0), 2.0*x-3.0, 2.0);
+
+    let ranges = vec![(10.0, 20.0), (20.0, 10.0), (-10.0, 0.0),
+        (-2000.0, -1000.0), (-10_000.0, 30_000.0), (0.0001, 0.0002), (-0.00001, 1.4999)];
+
+    for range in ranges {
+        let (xmin, f, nr_iterations) = newton_search(poly2, range.0, range.1, 0.0, 0);
+        let (_, _, nr_iterations_df) = brent_df_search(|x| (poly2(x).0, poly2(x).1), range.0, range.1, 0.0, 0);
+
+        println!("xmin: {:.8} f(xmin): {:6.2} iterations: {} vs brent_df {}",
+            xmin, f, nr_iterations, nr_iterations_df);
+
+        assert_float_relative_eq!(xmin, 1.5, 1.0e-14);
+        assert!(nr_iterations <= 1);
+    }
+
+    let cosine = |x: f64| (x.cos(), -x.sin(), -x.cos());
+    let (xmin, _, nr_iterations) = newton_search(cosine, 0.01, 1.0, 0.0, 0);
+    println!("cosine xmin: {} iterations: {}", xmin, nr_iterations);
+    assert_float_relative_eq!(xmin, std::f64::consts::PI, 1.0e-14);
+}
+
+#[cfg(test)]
+#[test]
+fn test_negative_curvature() {
+    // Lorentzian well, concave for |x| > 1/sqrt(3). From far away the bracket midpoint
+    // lies on the concave flank, where the Newton step points uphill and bisection is used.
+    let well = |x: f64| {
+        let q = 1.0 + x*x;
+        (-1.0/q, 2.0*x/(q*q), (2.0 - 6.0*x*x)/(q*q*q))
+    };
+
+    for range in [(5.0, 4.0), (-3.0, -2.5), (40.0, 30.0)] {
+        let (xmin, f, nr_iterations) = newton_search(well, range.0, range.1, 0.0, 0);
+        println!("xmin: {} f: {} iterations: {}", xmin, f, nr_iterations);
+
+        assert_float_absolute_eq!(xmin, 0.0, 1.0e-14);
+        assert_float_absolute_eq!(f, -1.0, 1.0e-15);
+    }
+}
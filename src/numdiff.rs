@@ -0,0 +1,248 @@
+//! Numerical derivatives by finite differences.
+//!
+//! (c) 2023 Igor Lesik
+//! MIT license
+//!
+//! References:
+//!
+//! 1. William H. Press - Numerical recipes, the art of scientific computing.
+//!    Cambridge University Press (2007). Section 5.7.
+//! 2. C. J. F. Ridders - Accurate computation of F'(x) and F'(x)F''(x).
+//!    Advances in Engineering Software 4 (1982).
+//!
+
+/// Finite-difference formula.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DiffMethod {
+    /// `(f(x + h) - f(x)) / h`, error `O(h)`.
+    Forward,
+    /// `(f(x + h) - f(x - h)) / 2h`, error `O(h^2)`.
+    Central,
+    /// Central differences extrapolated to `h = 0` by Ridders' method.
+    Richardson,
+}
+
+/// Numerical derivative with its error estimate.
+pub struct DiffRes {
+    /// Derivative.
+    pub df: f64,
+    /// Estimated absolute error of `df`, truncation plus rounding.
+    pub error: f64,
+    /// Number of function evaluations.
+    pub nr_evaluations: usize,
+}
+
+/// Numerical gradient with error estimates.
+pub struct GradientRes {
+    /// Gradient.
+    pub grad: Vec<f64>,
+    /// Estimated absolute error of every component of `grad`.
+    pub error: Vec<f64>,
+    /// Number of function evaluations.
+    pub nr_evaluations: usize,
+}
+
+/// Ridders' step reduction factor.
+const CON: f64 = 1.4_f64;
+/// Size of Ridders' extrapolation tableau.
+const NTAB: usize = 10;
+/// Ridders stops when the error grows by this factor.
+const SAFE: f64 = 2.0_f64;
+
+/// Numerical derivative `f'(x)`.
+///
+/// - William H. Press - Numerical recipes, the art of scientific computing (2007).
+/// - C. J. F. Ridders - Accurate computation of F'(x) and F'(x)F''(x) (1982).
+///
+/// `noise` is the relative error of the computed values of `f`, at least `f64::EPSILON`
+/// (used if 0). The step balances truncation and rounding error: `h = noise^(1/2) max(|x|, 1)`
+/// for forward and `h = noise^(1/3) max(|x|, 1)` for central differences. The truncation
+/// error is estimated from the difference with step `2h`. Richardson extrapolation starts
+/// from `h = 0.1 max(|x|, 1)` and reduces it until the extrapolated values stop improving.
+///
+/// # Example
+///
+/// ```
+/// use rustamath_mnmz::{derivative, DiffMethod};
+/// use assert_float_eq::*;
+/// let res = derivative(|x: f64| x.exp(), 1.0, DiffMethod::Richardson, 0.0);
+///
+/// assert_float_absolute_eq!(res.df, std::f64::consts::E, 1.0e-12);
+/// assert!(res.error < 1.0e-11);
+/// ```
+pub fn derivative<F: Fn (f64) -> f64>(fun: F, x: f64, method: DiffMethod, noise: f64) -> DiffRes {
+    let noise = noise.max(f64::EPSILON);
+    let scale = x.abs().max(1.0);
+    // Step exactly representable as the difference of two floating point numbers.
+    let exact = |h: f64| (x + h) - x;
+
+    match method {
+        DiffMethod::Forward => {
+            let h = exact(noise.sqrt() * scale);
+            let f0 = fun(x);
+            let f1 = fun(x + h);
+            let f2 = fun(x + 2.0 * h);
+            let df = (f1 - f0) / h;
+            let df2 = (f2 - f0) / (2.0 * h);
+            let rounding = 2.0 * noise * f0.abs().max(f1.abs()) / h;
+            DiffRes { df, error: (df - df2).abs() + rounding, nr_evaluations: 3 }
+        }
+        DiffMethod::Central => {
+            let h = exact(noise.cbrt() * scale);
+            let (fp, fm) = (fun(x + h), fun(x - h));
+            let (fp2, fm2) = (fun(x + 2.0 * h), fun(x - 2.0 * h));
+            let df = (fp - fm) / (2.0 * h);
+            let df2 = (fp2 - fm2) / (4.0 * h);
+            let rounding = noise * fp.abs().max(fm.abs()) / h;
+            DiffRes { df, error: (df - df2).abs() / 3.0 + rounding, nr_evaluations: 4 }
+        }
+        DiffMethod::Richardson => {
+            let mut h = exact(0.1 * scale);
+            let mut a = [[0.0_f64; NTAB]; NTAB];
+            a[0][0] = (fun(x + h) - fun(x - h)) / (2.0 * h);
+            let mut nr_evaluations = 2;
+            let mut df = a[0][0];
+            let mut error = f64::INFINITY;
+            for i in 1..NTAB {
+                h = exact(h / CON);
+                a[0][i] = (fun(x + h) - fun(x - h)) / (2.0 * h);
+                nr_evaluations += 2;
+                // Extrapolate the differences to zero step, each column one order higher.
+                let mut fac = CON * CON;
+                for j in 1..=i {
+                    a[j][i] = (a[j - 1][i] * fac - a[j - 1][i - 1]) / (fac - 1.0);
+                    fac *= CON * CON;
+                    let errt = (a[j][i] - a[j - 1][i]).abs().max((a[j][i] - a[j - 1][i - 1]).abs());
+                    if errt <= error {
+                        error = errt;
+                        df = a[j][i];
+                    }
+                }
+                // Higher order is worse by a significant factor, give up.
+                if (a[i][i] - a[i - 1][i - 1]).abs() >= SAFE * error {
+                    break;
+                }
+            }
+            DiffRes { df, error, nr_evaluations }
+        }
+    }
+}
+
+/// Numerical gradient of `f` at `x`.
+///
+/// Each component is the [`derivative`] of `f` along the coordinate axis.
+///
+/// # Example
+///
+/// ```
+/// use rustamath_mnmz::{gradient, DiffMethod};
+/// use assert_float_eq::*;
+/// let rosenbrock = |x: &[f64]| (1.0 - x[0]).powi(2) + 100.0*(x[1] - x[0]*x[0]).powi(2);
+///
+/// let res = gradient(rosenbrock, &[-1.2, 1.0], DiffMethod::Central, 0.0);
+///
+/// assert_float_absolute_eq!(res.grad[0], -215.6, 1.0e-6);
+/// assert_float_absolute_eq!(res.grad[1], -88.0, 1.0e-6);
+/// ```
+pub fn gradient<F: Fn (&[f64]) -> f64>(fun: F, x: &[f64], method: DiffMethod, noise: f64) -> GradientRes {
+    let mut grad = vec![0.0; x.len()];
+    let mut error = vec![0.0; x.len()];
+    let mut nr_evaluations: usize = 0;
+    for i in 0..x.len() {
+        let res = derivative(|t| {
+            let mut xt = x.to_vec();
+            xt[i] = t;
+            fun(&xt)
+        }, x[i], method, noise);
+        grad[i] = res.df;
+        error[i] = res.error;
+        nr_evaluations += res.nr_evaluations;
+    }
+    GradientRes { grad, error, nr_evaluations }
+}
+
+/// Turn `f(x)` into the `(f(x), f'(x))` form that
+/// [`brent_df_search`](crate::brent_df_search) expects, with numerical derivatives.
+///
+/// # Example
+///
+/// ```
+/// use rustamath_mnmz::{brent_df_search, with_derivative, DiffMethod};
+/// use assert_float_eq::*;
+/// let cosine = with_derivative(|x: f64| x.cos(), DiffMethod::Central, 0.0);
+///
+/// let (xmin, _, _) = brent_df_search(cosine, 0.01, 1.0, 0.0, 0);
+///
+/// assert_float_relative_eq!(xmin, std::f64::consts::PI, 1.0e-8);
+/// ```
+pub fn with_derivative<F: Fn (f64) -> f64>(fun: F, method: DiffMethod, noise: f64) -> impl Fn (f64) -> (f64, f64) {
+    move |x| (fun(x), derivative(&fun, x, method, noise).df)
+}
+
+/// Turn `f(x)` into the `fun(x, grad) -> f(x)` form of the gradient methods, such as
+/// [`lbfgsb`](crate::lbfgsb), with numerical gradients.
+pub fn with_gradient<F: Fn (&[f64]) -> f64>(fun: F, method: DiffMethod, noise: f64) -> impl Fn (&[f64], &mut [f64]) -> f64 {
+    move |x, g| {
+        g.copy_from_slice(&gradient(&fun, x, method, noise).grad);
+        fun(x)
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_exp_accuracy() {
+    let e = std::f64::consts::E;
+    for (method, accuracy) in [
+        (DiffMethod::Forward, 1.0e-7),
+        (DiffMethod::Central, 1.0e-10),
+        (DiffMethod::Richardson, 1.0e-13),
+    ] {
+        let res = derivative(|x: f64| x.exp(), 1.0, method, 0.0);
+        let actual = (res.df - e).abs();
+        println!("{:?}: df: {} error: {:e} estimate: {:e} evaluations: {}",
+            method, res.df, actual, res.error, res.nr_evaluations);
+        assert!(actual < accuracy);
+        assert!(actual <= res.error);
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_noisy_function() {
+    use super::sampling::{Rng, SeedableRng, SplitMix64};
+
+    // sin(x) with deterministic noise of relative size 1e-8.
+    let noisy = |x: f64| x.sin() * (1.0 + 1.0e-8 * SplitMix64::seed_from_u64(x.to_bits()).uniform(-1.0, 1.0));
+    let exact = 2.0_f64.cos();
+
+    let naive = derivative(noisy, 2.0, DiffMethod::Forward, 0.0);
+    let aware = derivative(noisy, 2.0, DiffMethod::Forward, 1.0e-8);
+    println!("naive: {:e} (estimate {:e}) aware: {:e} (estimate {:e})",
+        (naive.df - exact).abs(), naive.error, (aware.df - exact).abs(), aware.error);
+    assert!((aware.df - exact).abs() < 1.0e-3);
+    assert!((aware.df - exact).abs() < (naive.df - exact).abs());
+    assert!((aware.df - exact).abs() <= aware.error);
+
+    let central = derivative(noisy, 2.0, DiffMethod::Central, 1.0e-8);
+    println!("central: {:e} (estimate {:e})", (central.df - exact).abs(), central.error);
+    assert!((central.df - exact).abs() < 1.0e-5);
+    assert!((central.df - exact).abs() <= central.error);
+}
+
+#[cfg(test)]
+#[test]
+fn test_gradient_adapter() {
+    use super::lbfgsb;
+
+    let rosenbrock = |x: &[f64]| (1.0 - x[0]).powi(2) + 100.0*(x[1] - x[0]*x[0]).powi(2);
+    let res = gradient(rosenbrock, &[0.5, 0.5], DiffMethod::Richardson, 0.0);
+    assert_float_absolute_eq!(res.grad[0], -51.0, 1.0e-9);
+    assert_float_absolute_eq!(res.grad[1], 50.0, 1.0e-9);
+
+    let fun = with_gradient(rosenbrock, DiffMethod::Central, 0.0);
+    let res = lbfgsb(fun, &[-1.2, 1.0], &[-2.0, -2.0], &[2.0, 2.0], 0, 1.0e-7, 0);
+    println!("x: {:?} pg: {} iterations: {}", res.xmin, res.pg_norm, res.nr_iterations);
+    assert!(res.converged);
+    assert_float_absolute_eq!(res.xmin[0], 1.0, 1.0e-6);
+    assert_float_absolute_eq!(res.xmin[1], 1.0, 1.0e-6);
+}
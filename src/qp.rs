@@ -0,0 +1,350 @@
+//! Convex quadratic programming by the primal active-set method.
+//!
+//! (c) 2023 Igor Lesik
+//! MIT license
+//!
+//! References:
+//!
+//! 1. Jorge Nocedal, Stephen J. Wright - Numerical Optimization. Springer (2006).
+//!    Chapter 16.
+//!
+use super::linalg::{Matrix, lu_solve};
+use super::lp::{linprog, LpStatus};
+
+/// Steps and multipliers smaller than this are treated as zero.
+const EPS: f64 = 1.0e-10_f64;
+
+/// Outcome of a quadratic program.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum QpStatus {
+    /// Optimal solution found.
+    Optimal,
+    /// No point satisfies the constraints.
+    Infeasible,
+    /// The KKT system is singular or the objective is unbounded below: `Q` is not positive
+    /// definite on the null space of the working constraints.
+    Singular,
+    /// Iteration limit reached before optimality.
+    MaxIterations,
+}
+
+/// Result of a quadratic program.
+pub struct QpRes {
+    /// Outcome of the solve.
+    pub status: QpStatus,
+    /// Solution, the last iterate unless the status is `Infeasible`.
+    pub x: Vec<f64>,
+    /// Objective value `x^T Q x / 2 + c^T x`.
+    pub fun: f64,
+    /// Lagrange multipliers of the inequality constraints, all `>= 0`.
+    pub lambda_ub: Vec<f64>,
+    /// Lagrange multipliers of the equality constraints.
+    pub lambda_eq: Vec<f64>,
+    /// Indices of the inequality constraints in the final working (active) set.
+    pub active: Vec<usize>,
+    /// Number of active-set iterations.
+    pub nr_iterations: usize,
+}
+
+/// Convex quadratic programming by the primal active-set method.
+///
+/// - Jorge Nocedal, Stephen J. Wright - Numerical Optimization. Springer (2006).
+///
+/// Solves
+///
+/// ```text
+/// minimize x^T Q x / 2 + c^T x  subject to  A_ub x <= b_ub,  A_eq x = b_eq
+/// ```
+///
+/// with symmetric positive semi-definite `Q` given by rows. A feasible starting vertex is found
+/// by [`linprog`](crate::linprog). Each iteration minimizes the objective with the constraints of
+/// the working set held as equalities; the step is cut short by the first blocking constraint,
+/// which joins the working set, and a constraint with a negative multiplier leaves it.
+///
+/// The multipliers satisfy `Q x + c + A_eq^T lambda_eq + A_ub^T lambda_ub = 0`.
+///
+/// At most `max_iterations` iterations are made (1000 if 0).
+///
+/// # Example
+///
+/// ```
+/// use rustamath_mnmz::{quadprog, QpStatus};
+/// use assert_float_eq::*;
+/// // minimize (x - 1)^2 + (y - 2.5)^2  subject to  -x + 2y <= 2,  x + 2y <= 6,  x - 2y <= 2,  x, y >= 0
+/// let q = [vec![2.0, 0.0], vec![0.0, 2.0]];
+/// let a_ub = [vec![-1.0, 2.0], vec![1.0, 2.0], vec![1.0, -2.0], vec![-1.0, 0.0], vec![0.0, -1.0]];
+///
+/// let res = quadprog(&q, &[-2.0, -5.0], &a_ub, &[2.0, 6.0, 2.0, 0.0, 0.0], &[], &[], 0);
+///
+/// assert_eq!(res.status, QpStatus::Optimal);
+/// assert_float_absolute_eq!(res.x[0], 1.4, 1.0e-10);
+/// assert_float_absolute_eq!(res.x[1], 1.7, 1.0e-10);
+/// assert_eq!(res.active, vec![0]);
+/// assert_float_absolute_eq!(res.lambda_ub[0], 0.8, 1.0e-10);
+/// ```
+#[allow(clippy::needless_range_loop)]
+pub fn quadprog(
+    q: &[Vec<f64>],
+    c: &[f64],
+    a_ub: &[Vec<f64>],
+    b_ub: &[f64],
+    a_eq: &[Vec<f64>],
+    b_eq: &[f64],
+    max_iterations: usize
+) -> QpRes
+{
+    let max_iterations = if max_iterations < 1 { 1000 } else { max_iterations };
+
+    let n = c.len();
+    let m_ub = b_ub.len();
+    let m_eq = b_eq.len();
+
+    let dot = |a: &[f64], b: &[f64]| a.iter().zip(b.iter()).map(|(x, y)| x * y).sum::<f64>();
+    let objective = |x: &[f64]| {
+        (0..n).map(|i| x[i] * (0.5 * dot(&q[i], x) + c[i])).sum::<f64>()
+    };
+
+    // Feasible vertex from phase one of the simplex method, with free variables split
+    // into positive and negative parts.
+    let split = |rows: &[Vec<f64>]| -> Vec<Vec<f64>> {
+        rows.iter().map(|r| r.iter().copied().chain(r.iter().map(|a| -a)).collect()).collect()
+    };
+    let lp = linprog(&vec![0.0; 2 * n], &split(a_ub), b_ub, &split(a_eq), b_eq, 0);
+    if lp.status != LpStatus::Optimal {
+        return QpRes {
+            status: QpStatus::Infeasible,
+            x: vec![0.0; n],
+            fun: f64::NAN,
+            lambda_ub: vec![0.0; m_ub],
+            lambda_eq: vec![0.0; m_eq],
+            active: Vec::new(),
+            nr_iterations: 0,
+        };
+    }
+    let mut x: Vec<f64> = (0..n).map(|i| lp.x[i] - lp.x[n + i]).collect();
+
+    // Working set of inequality constraints; equality constraints are always in it.
+    // It starts with the constraints active at the vertex that are linearly independent
+    // of those already taken, so that a semi-definite Q is still pinned down.
+    let mut working: Vec<usize> = Vec::new();
+    let mut basis: Vec<Vec<f64>> = Vec::new();
+    let rows_eq = a_eq.iter().map(|r| (None, r));
+    let rows_active = (0..m_ub)
+        .filter(|&i| b_ub[i] - dot(&a_ub[i], &x) <= EPS * (1.0 + b_ub[i].abs()))
+        .map(|i| (Some(i), &a_ub[i]));
+    for (index, row) in rows_eq.chain(rows_active) {
+        let mut v = row.clone();
+        for u in basis.iter() {
+            let d = dot(u, &v);
+            for (vj, uj) in v.iter_mut().zip(u.iter()) {
+                *vj -= d * uj;
+            }
+        }
+        let norm = dot(&v, &v).sqrt();
+        if norm > 1.0e-8 * (1.0 + dot(row, row).sqrt()) {
+            v.iter_mut().for_each(|vj| *vj /= norm);
+            basis.push(v);
+            if let Some(i) = index {
+                working.push(i);
+            }
+        }
+    }
+    let mut lambda_ub = vec![0.0; m_ub];
+    let mut lambda_eq = vec![0.0; m_eq];
+    let mut status = QpStatus::MaxIterations;
+    let mut nr_iterations: usize = 0;
+
+    // KKT matrix [Q A^T; A 0] of the equality constrained problem on the working set.
+    let kkt_matrix = |working: &[usize]| {
+        let nw = m_eq + working.len();
+        let mut kkt = Matrix::new(n + nw, n + nw);
+        for i in 0..n {
+            for j in 0..n {
+                kkt.set(i, j, q[i][j]);
+            }
+        }
+        for k in 0..nw {
+            let row = if k < m_eq { &a_eq[k] } else { &a_ub[working[k - m_eq]] };
+            for j in 0..n {
+                kkt.set(n + k, j, row[j]);
+                kkt.set(j, n + k, row[j]);
+            }
+        }
+        kkt
+    };
+
+    while nr_iterations < max_iterations {
+        nr_iterations += 1;
+
+        // Equality constrained step: [Q A^T; A 0] [p; lambda] = [-(Q x + c); 0].
+        let mut rhs = vec![0.0; n + m_eq + working.len()];
+        for i in 0..n {
+            rhs[i] = -(dot(&q[i], &x) + c[i]);
+        }
+        if !lu_solve(&mut kkt_matrix(&working), &mut rhs) {
+            status = QpStatus::Singular;
+            break;
+        }
+        let (mut p, lambda) = rhs.split_at(n);
+        let mut max_step = 1.0;
+        let drop_direction: Vec<f64>;
+
+        let xnorm = x.iter().fold(0.0_f64, |s, xi| s.max(xi.abs()));
+        if p.iter().all(|pi| pi.abs() <= EPS * (1.0 + xnorm)) {
+            // Stationary on the working set: optimal unless a multiplier is negative.
+            let most_negative = (0..working.len())
+                .min_by(|&a, &b| lambda[m_eq + a].total_cmp(&lambda[m_eq + b]))
+                .filter(|&k| lambda[m_eq + k] < -EPS);
+
+            let Some(k) = most_negative else {
+                lambda_eq.copy_from_slice(&lambda[..m_eq]);
+                for (k, &i) in working.iter().enumerate() {
+                    lambda_ub[i] = lambda[m_eq + k];
+                }
+                status = QpStatus::Optimal;
+                break;
+            };
+
+            // Leave constraint k along the direction that keeps the rest of the working set
+            // active, [Q A^T; A 0] [p; mu] = [0; -e_k]. The objective falls along it at rate
+            // lambda_k with curvature mu_k, so the line minimum is at -lambda_k / mu_k; with
+            // no curvature the step is limited by the constraints alone. Unlike dropping the
+            // constraint outright, this works when Q is only semi-definite.
+            let mut rhs = vec![0.0; n + m_eq + working.len()];
+            rhs[n + m_eq + k] = -1.0;
+            if !lu_solve(&mut kkt_matrix(&working), &mut rhs) {
+                status = QpStatus::Singular;
+                break;
+            }
+            let curvature = rhs[n + m_eq + k];
+            max_step = if curvature > EPS { -lambda[m_eq + k] / curvature } else { f64::INFINITY };
+            rhs.truncate(n);
+            drop_direction = rhs;
+            p = &drop_direction;
+            working.remove(k);
+        }
+
+        // Longest step along p that keeps the constraints outside the working set.
+        let mut alpha = max_step;
+        let mut blocking = None;
+        for i in (0..m_ub).filter(|i| !working.contains(i)) {
+            let ap = dot(&a_ub[i], p);
+            if ap > EPS {
+                let step = ((b_ub[i] - dot(&a_ub[i], &x)) / ap).max(0.0);
+                if step < alpha {
+                    alpha = step;
+                    blocking = Some(i);
+                }
+            }
+        }
+        if alpha.is_infinite() {
+            // The objective decreases without bound along p.
+            status = QpStatus::Singular;
+            break;
+        }
+        for (xi, pi) in x.iter_mut().zip(p.iter()) {
+            *xi += alpha * pi;
+        }
+        if let Some(i) = blocking {
+            working.push(i);
+        }
+    }
+
+    working.sort_unstable();
+
+    QpRes {
+        status,
+        fun: objective(&x),
+        x,
+        lambda_ub,
+        lambda_eq,
+        active: working,
+        nr_iterations,
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_nocedal_wright_16_4() {
+    let q = [vec![2.0, 0.0], vec![0.0, 2.0]];
+    let a_ub = [vec![-1.0, 2.0], vec![1.0, 2.0], vec![1.0, -2.0], vec![-1.0, 0.0], vec![0.0, -1.0]];
+    let b_ub = [2.0, 6.0, 2.0, 0.0, 0.0];
+
+    let res = quadprog(&q, &[-2.0, -5.0], &a_ub, &b_ub, &[], &[], 0);
+
+    println!("x: {:?} fun: {} lambda: {:?} active: {:?} iterations: {}",
+        res.x, res.fun, res.lambda_ub, res.active, res.nr_iterations);
+
+    assert_eq!(res.status, QpStatus::Optimal);
+    assert_float_absolute_eq!(res.x[0], 1.4, 1.0e-10);
+    assert_float_absolute_eq!(res.x[1], 1.7, 1.0e-10);
+    assert_float_absolute_eq!(res.fun, 0.8 - 7.25, 1.0e-10);
+    assert_eq!(res.active, vec![0]);
+    assert_float_absolute_eq!(res.lambda_ub[0], 0.8, 1.0e-10);
+    assert!(res.lambda_ub[1..].iter().all(|&l| l == 0.0));
+}
+
+#[cfg(test)]
+#[test]
+fn test_portfolio() {
+    // Minimum variance portfolio with the expected return at least 0.1 and no short sales.
+    let sigma = [
+        vec![0.04, 0.006, 0.002, 0.0],
+        vec![0.006, 0.09, 0.01, 0.004],
+        vec![0.002, 0.01, 0.0225, 0.003],
+        vec![0.0, 0.004, 0.003, 0.01],
+    ];
+    let mu = [0.12, 0.18, 0.08, 0.05];
+    let mut a_ub = vec![mu.iter().map(|m| -m).collect::<Vec<f64>>()];
+    let mut b_ub = vec![-0.1];
+    for i in 0..4 {
+        let mut row = vec![0.0; 4];
+        row[i] = -1.0;
+        a_ub.push(row);
+        b_ub.push(0.0);
+    }
+
+    let res = quadprog(&sigma, &[0.0; 4], &a_ub, &b_ub, &[vec![1.0; 4]], &[1.0], 0);
+
+    println!("w: {:?} variance: {} lambda: {:?} {:?} active: {:?} iterations: {}",
+        res.x, 2.0 * res.fun, res.lambda_ub, res.lambda_eq, res.active, res.nr_iterations);
+
+    assert_eq!(res.status, QpStatus::Optimal);
+    assert_float_absolute_eq!(res.x.iter().sum::<f64>(), 1.0, 1.0e-12);
+    assert!(res.x.iter().all(|&w| w >= -1.0e-12));
+
+    // KKT conditions: stationarity, dual feasibility and complementary slackness.
+    for j in 0..4 {
+        let mut grad: f64 = (0..4).map(|k| sigma[j][k] * res.x[k]).sum();
+        grad += res.lambda_eq[0];
+        for (row, l) in a_ub.iter().zip(res.lambda_ub.iter()) {
+            grad += row[j] * l;
+        }
+        assert_float_absolute_eq!(grad, 0.0, 1.0e-12);
+    }
+    for i in 0..a_ub.len() {
+        let slack = b_ub[i] - (0..4).map(|j| a_ub[i][j] * res.x[j]).sum::<f64>();
+        assert!(res.lambda_ub[i] >= 0.0 && slack >= -1.0e-12);
+        assert_float_absolute_eq!(res.lambda_ub[i] * slack, 0.0, 1.0e-12);
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_infeasible_singular() {
+    let q = [vec![1.0, 0.0], vec![0.0, 1.0]];
+    let res = quadprog(&q, &[0.0, 0.0], &[vec![1.0, 1.0], vec![-1.0, -1.0]], &[1.0, -2.0], &[], &[], 0);
+    assert_eq!(res.status, QpStatus::Infeasible);
+
+    // Linear program in disguise: the vertex start keeps the KKT system regular.
+    let res = quadprog(&[vec![0.0, 0.0], vec![0.0, 0.0]], &[-1.0, -1.0],
+        &[vec![1.0, 2.0], vec![3.0, 1.0], vec![-1.0, 0.0], vec![0.0, -1.0]], &[4.0, 6.0, 0.0, 0.0], &[], &[], 0);
+    assert_eq!(res.status, QpStatus::Optimal);
+    assert_float_absolute_eq!(res.x[0], 1.6, 1.0e-10);
+    assert_float_absolute_eq!(res.x[1], 1.2, 1.0e-10);
+
+    // Linear objective in y with nothing to stop it.
+    let q = [vec![1.0, 0.0], vec![0.0, 0.0]];
+    let res = quadprog(&q, &[0.0, -1.0], &[vec![1.0, 0.0]], &[1.0], &[], &[], 0);
+    assert_eq!(res.status, QpStatus::Singular);
+}
@@ -0,0 +1,82 @@
+//! Float-like numbers for objectives written once and evaluated with derivatives.
+//!
+//! (c) 2023 Igor Lesik
+//! MIT license
+//!
+use std::ops::{Add, Sub, Mul, Div, Neg, AddAssign, SubAssign, MulAssign, DivAssign};
+
+/// Arithmetic and elementary functions shared by `f64` and the automatic differentiation
+/// types, such as [`Dual`](crate::Dual).
+///
+/// An objective written as `fn f<T: Real>(x: T) -> T` can be evaluated as a plain `f64`
+/// function or with derivatives. Constants enter on the right of an operator, `x * 2.0`,
+/// or through [`Real::from_f64`].
+///
+/// # Example
+///
+/// ```
+/// use rustamath_mnmz::Real;
+/// fn logistic<T: Real>(x: T) -> T {
+///     T::from_f64(1.0) / ((-x).exp() + 1.0)
+/// }
+/// assert_eq!(logistic(0.0_f64), 0.5);
+/// ```
+pub trait Real:
+    Copy
+    + PartialOrd
+    + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self> + Div<Output = Self>
+    + Neg<Output = Self>
+    + Add<f64, Output = Self> + Sub<f64, Output = Self> + Mul<f64, Output = Self> + Div<f64, Output = Self>
+    + AddAssign + SubAssign + MulAssign + DivAssign
+{
+    /// Constant with the value `v`.
+    fn from_f64(v: f64) -> Self;
+    /// Value without derivatives.
+    fn value(&self) -> f64;
+
+    /// Absolute value.
+    fn abs(self) -> Self;
+    /// Square root.
+    fn sqrt(self) -> Self;
+    /// Exponential `e^x`.
+    fn exp(self) -> Self;
+    /// Natural logarithm.
+    fn ln(self) -> Self;
+    /// Integer power `x^n`.
+    fn powi(self, n: i32) -> Self;
+    /// Real power `x^p`.
+    fn powf(self, p: f64) -> Self;
+    /// Sine.
+    fn sin(self) -> Self;
+    /// Cosine.
+    fn cos(self) -> Self;
+    /// Tangent.
+    fn tan(self) -> Self;
+    /// Arctangent.
+    fn atan(self) -> Self;
+    /// Hyperbolic sine.
+    fn sinh(self) -> Self;
+    /// Hyperbolic cosine.
+    fn cosh(self) -> Self;
+    /// Hyperbolic tangent.
+    fn tanh(self) -> Self;
+}
+
+impl Real for f64 {
+    fn from_f64(v: f64) -> Self { v }
+    fn value(&self) -> f64 { *self }
+
+    fn abs(self) -> Self { f64::abs(self) }
+    fn sqrt(self) -> Self { f64::sqrt(self) }
+    fn exp(self) -> Self { f64::exp(self) }
+    fn ln(self) -> Self { f64::ln(self) }
+    fn powi(self, n: i32) -> Self { f64::powi(self, n) }
+    fn powf(self, p: f64) -> Self { f64::powf(self, p) }
+    fn sin(self) -> Self { f64::sin(self) }
+    fn cos(self) -> Self { f64::cos(self) }
+    fn tan(self) -> Self { f64::tan(self) }
+    fn atan(self) -> Self { f64::atan(self) }
+    fn sinh(self) -> Self { f64::sinh(self) }
+    fn cosh(self) -> Self { f64::cosh(self) }
+    fn tanh(self) -> Self { f64::tanh(self) }
+}
@@ -0,0 +1,388 @@
+//! Random and quasi-random sampling.
+//!
+//! (c) 2023 Igor Lesik
+//! MIT license
+//!
+//! References:
+//!
+//! 1. William H. Press - Numerical recipes, the art of scientific computing.
+//!    Cambridge University Press (2007).
+//! 2. Guy L. Steele, Doug Lea, Christine H. Flood - Fast splittable pseudorandom
+//!    number generators. OOPSLA (2014).
+//! 3. Stephen Joe, Frances Y. Kuo - Constructing Sobol sequences with better
+//!    two-dimensional projections. SIAM J. Sci. Comput. 30 (2008).
+//! 4. Michael D. McKay, Richard J. Beckman, William J. Conover - A comparison of three
+//!    methods for selecting values of input variables in the analysis of output from
+//!    a computer code. Technometrics 21 (1979).
+//!
+
+/// Source of random bits for the stochastic methods.
+///
+/// Only [`Rng::next_u64`] has to be implemented. Every stochastic method of the crate draws
+/// its numbers through this trait, so a run is reproducible bit for bit given the same
+/// generator state.
+pub trait Rng {
+    /// Next 64 random bits.
+    fn next_u64(&mut self) -> u64;
+
+    /// Uniform number in `[0, 1)` built from the upper 53 bits.
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 * (1.0 / (1u64 << 53) as f64)
+    }
+
+    /// Uniform number in `[a, b)`.
+    fn uniform(&mut self, a: f64, b: f64) -> f64 {
+        a + (b - a) * self.next_f64()
+    }
+
+    /// Uniform integer in `0..n`, `n` must be positive.
+    fn below(&mut self, n: usize) -> usize {
+        // Multiply-shift mapping of 64 random bits onto `0..n`.
+        ((self.next_u64() as u128 * n as u128) >> 64) as usize
+    }
+}
+
+/// Generator that can be created from a 64-bit seed.
+pub trait SeedableRng: Rng {
+    /// New generator, the same seed gives the same sequence on every platform.
+    fn seed_from_u64(seed: u64) -> Self;
+}
+
+/// SplitMix64 generator of Steele, Lea and Flood.
+///
+/// Small and fast, passes BigCrush; the default generator of the crate.
+#[derive(Clone, Debug)]
+pub struct SplitMix64 {
+    state: u64,
+}
+
+impl Rng for SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+}
+
+impl SeedableRng for SplitMix64 {
+    fn seed_from_u64(seed: u64) -> Self {
+        SplitMix64 { state: seed }
+    }
+}
+
+/// Adapter that lets any `rand_core::RngCore` generator drive the methods of the crate,
+/// for example `RandCoreRng(StdRng::seed_from_u64(42))`.
+///
+/// Available with the `rand` feature.
+#[cfg(feature = "rand")]
+#[derive(Clone, Debug)]
+pub struct RandCoreRng<R>(pub R);
+
+#[cfg(feature = "rand")]
+impl<R: rand_core::RngCore> Rng for RandCoreRng<R> {
+    fn next_u64(&mut self) -> u64 {
+        self.0.next_u64()
+    }
+}
+
+/// Latin hypercube design of `n` points in the unit hypercube of `ndim` dimensions.
+///
+/// Along every dimension each of the `n` equal strata holds exactly one point,
+/// placed at random inside its stratum.
+///
+/// # Example
+///
+/// ```
+/// use rustamath_mnmz::sampling::{latin_hypercube, SplitMix64, SeedableRng};
+/// let mut rng = SplitMix64::seed_from_u64(1);
+/// let points = latin_hypercube(5, 3, &mut rng);
+///
+/// for d in 0..3 {
+///     let mut strata: Vec<usize> = points.iter().map(|p| (p[d] * 5.0) as usize).collect();
+///     strata.sort();
+///     assert_eq!(strata, vec![0, 1, 2, 3, 4]);
+/// }
+/// ```
+pub fn latin_hypercube<R: Rng>(n: usize, ndim: usize, rng: &mut R) -> Vec<Vec<f64>> {
+    let mut points = vec![vec![0.0; ndim]; n];
+    let mut perm: Vec<usize> = (0..n).collect();
+
+    for d in 0..ndim {
+        // Fisher-Yates shuffle of the strata.
+        for i in (1..n).rev() {
+            let j = rng.below(i + 1);
+            perm.swap(i, j);
+        }
+        for (point, &stratum) in points.iter_mut().zip(perm.iter()) {
+            point[d] = (stratum as f64 + rng.next_f64()) / n as f64;
+        }
+    }
+
+    points
+}
+
+/// First `n` points of the Halton sequence in `ndim` dimensions, skipping the first `skip`.
+///
+/// Dimension `d` is the radical inverse of the point index in the `d`-th prime base.
+/// The sequence starts at index 1, so the origin is never returned.
+///
+/// # Example
+///
+/// ```
+/// use rustamath_mnmz::sampling::halton;
+/// let points = halton(3, 2, 0);
+/// assert_eq!(points[0], vec![0.5, 1.0/3.0]);
+/// assert_eq!(points[1], vec![0.25, 2.0/3.0]);
+/// assert_eq!(points[2], vec![0.75, 1.0/9.0]);
+/// ```
+pub fn halton(n: usize, ndim: usize, skip: usize) -> Vec<Vec<f64>> {
+    let bases = primes(ndim);
+
+    (skip+1..=skip+n)
+        .map(|index| bases.iter().map(|&b| radical_inverse(index as u64, b)).collect())
+        .collect()
+}
+
+fn radical_inverse(mut index: u64, base: u64) -> f64 {
+    let inv_base = 1.0 / base as f64;
+    let mut f = inv_base;
+    let mut x = 0.0;
+    while index > 0 {
+        x += (index % base) as f64 * f;
+        index /= base;
+        f *= inv_base;
+    }
+    x
+}
+
+fn primes(n: usize) -> Vec<u64> {
+    let mut primes: Vec<u64> = Vec::with_capacity(n);
+    let mut candidate = 2;
+    while primes.len() < n {
+        if primes.iter().take_while(|&&p| p * p <= candidate).all(|&p| candidate % p != 0) {
+            primes.push(candidate);
+        }
+        candidate += 1;
+    }
+    primes
+}
+
+/// Number of bits of the Sobol points.
+const SOBOL_BITS: usize = 32;
+
+/// Primitive polynomials and initial direction numbers `(s, a, m)` of Joe and Kuo
+/// for dimensions 2 and up; dimension 1 is the van der Corput sequence.
+const SOBOL_DIRECTIONS: [(u32, u32, &[u32]); 20] = [
+    (1, 0, &[1]),
+    (2, 1, &[1, 3]),
+    (3, 1, &[1, 3, 1]),
+    (3, 2, &[1, 1, 1]),
+    (4, 1, &[1, 1, 3, 3]),
+    (4, 4, &[1, 3, 5, 13]),
+    (5, 2, &[1, 1, 5, 5, 17]),
+    (5, 4, &[1, 1, 5, 5, 5]),
+    (5, 7, &[1, 1, 7, 11, 19]),
+    (5, 11, &[1, 1, 5, 1, 1]),
+    (5, 13, &[1, 1, 1, 3, 11]),
+    (5, 14, &[1, 3, 5, 5, 31]),
+    (6, 1, &[1, 3, 3, 9, 7, 49]),
+    (6, 13, &[1, 1, 1, 15, 21, 21]),
+    (6, 16, &[1, 3, 1, 13, 27, 49]),
+    (6, 19, &[1, 1, 1, 15, 7, 5]),
+    (6, 22, &[1, 3, 1, 15, 13, 25]),
+    (6, 25, &[1, 1, 5, 5, 19, 61]),
+    (7, 1, &[1, 3, 7, 11, 23, 15, 103]),
+    (7, 4, &[1, 3, 7, 13, 13, 15, 69]),
+];
+
+/// Generator of the Sobol low-discrepancy sequence.
+///
+/// - Stephen Joe, Frances Y. Kuo - Constructing Sobol sequences with better
+///   two-dimensional projections (2008).
+///
+/// Points are produced in Gray-code order (Antonov-Saleev), the first point is the origin.
+/// The first `2^k` points of every dimension fall one per interval of length `2^-k`.
+///
+/// # Example
+///
+/// ```
+/// use rustamath_mnmz::sampling::Sobol;
+/// let mut sobol = Sobol::new(2).unwrap();
+/// let points: Vec<Vec<f64>> = (0..4).map(|_| sobol.next_point()).collect();
+/// assert_eq!(points, vec![vec![0.0, 0.0], vec![0.5, 0.5], vec![0.75, 0.25], vec![0.25, 0.75]]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct Sobol {
+    directions: Vec<[u32; SOBOL_BITS]>,
+    state: Vec<u32>,
+    index: u64,
+}
+
+impl Sobol {
+    /// Largest number of dimensions supported.
+    pub const MAX_DIMENSIONS: usize = SOBOL_DIRECTIONS.len() + 1;
+
+    /// New generator of `ndim`-dimensional points, `None` if `ndim` exceeds
+    /// [`Sobol::MAX_DIMENSIONS`].
+    pub fn new(ndim: usize) -> Option<Self> {
+        if ndim > Self::MAX_DIMENSIONS {
+            return None;
+        }
+
+        let mut directions = Vec::with_capacity(ndim);
+        for d in 0..ndim {
+            let mut v = [0u32; SOBOL_BITS];
+            if d == 0 {
+                for (k, vk) in v.iter_mut().enumerate() {
+                    *vk = 1 << (SOBOL_BITS - 1 - k);
+                }
+            }
+            else {
+                let (s, a, m) = SOBOL_DIRECTIONS[d - 1];
+                let s = s as usize;
+                for k in 0..s {
+                    v[k] = m[k] << (SOBOL_BITS - 1 - k);
+                }
+                for k in s..SOBOL_BITS {
+                    v[k] = v[k - s] ^ (v[k - s] >> s);
+                    for j in 1..s {
+                        if (a >> (s - 1 - j)) & 1 == 1 {
+                            v[k] ^= v[k - j];
+                        }
+                    }
+                }
+            }
+            directions.push(v);
+        }
+
+        Some(Sobol { directions, state: vec![0; ndim], index: 0 })
+    }
+
+    /// Next point of the sequence.
+    pub fn next_point(&mut self) -> Vec<f64> {
+        let scale = 1.0 / (1u64 << SOBOL_BITS) as f64;
+        let point = self.state.iter().map(|&x| x as f64 * scale).collect();
+
+        // Flip the direction bit of the lowest zero bit of the index.
+        let c = (!self.index).trailing_zeros() as usize;
+        if c < SOBOL_BITS {
+            for (x, v) in self.state.iter_mut().zip(self.directions.iter()) {
+                *x ^= v[c];
+            }
+        }
+        self.index += 1;
+
+        point
+    }
+}
+
+/// First `n` points of the Sobol sequence in `ndim` dimensions.
+///
+/// Returns `None` if `ndim` exceeds [`Sobol::MAX_DIMENSIONS`].
+pub fn sobol(n: usize, ndim: usize) -> Option<Vec<Vec<f64>>> {
+    let mut generator = Sobol::new(ndim)?;
+    Some((0..n).map(|_| generator.next_point()).collect())
+}
+
+/// Map points of the unit hypercube onto the box `lower[i] <= x[i] <= upper[i]`.
+pub fn scale_to_box(points: &mut [Vec<f64>], lower: &[f64], upper: &[f64]) {
+    for point in points.iter_mut() {
+        for (i, x) in point.iter_mut().enumerate() {
+            *x = lower[i] + *x * (upper[i] - lower[i]);
+        }
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_seed_reproducible() {
+    let mut rng1 = SplitMix64::seed_from_u64(123);
+    let mut rng2 = SplitMix64::seed_from_u64(123);
+    let mut rng3 = SplitMix64::seed_from_u64(124);
+
+    let a: Vec<u64> = (0..10).map(|_| rng1.next_u64()).collect();
+    let b: Vec<u64> = (0..10).map(|_| rng2.next_u64()).collect();
+    let c: Vec<u64> = (0..10).map(|_| rng3.next_u64()).collect();
+
+    assert_eq!(a, b);
+    assert_ne!(a, c);
+
+    // Reference value of SplitMix64 seeded with 0.
+    assert_eq!(SplitMix64::seed_from_u64(0).next_u64(), 0xe220_a839_7b1d_cdaf);
+
+    let mut mean = 0.0;
+    for _ in 0..10_000 {
+        let x = rng1.next_f64();
+        assert!((0.0..1.0).contains(&x));
+        assert!(rng1.below(7) < 7);
+        mean += x / 10_000.0;
+    }
+    assert_float_absolute_eq!(mean, 0.5, 1.0e-2);
+}
+
+#[cfg(all(test, feature = "rand"))]
+#[test]
+fn test_rand_core_rng() {
+    use rand_core::SeedableRng as _;
+
+    let mut rng1 = RandCoreRng(rand_pcg::Pcg64::seed_from_u64(42));
+    let mut rng2 = RandCoreRng(rand_pcg::Pcg64::seed_from_u64(42));
+    let mut rng3 = RandCoreRng(rand_pcg::Pcg64::seed_from_u64(43));
+
+    let a: Vec<f64> = (0..100).map(|_| rng1.uniform(-2.0, 3.0)).collect();
+    let b: Vec<f64> = (0..100).map(|_| rng2.uniform(-2.0, 3.0)).collect();
+    let c: Vec<f64> = (0..100).map(|_| rng3.uniform(-2.0, 3.0)).collect();
+
+    assert_eq!(a, b);
+    assert_ne!(a, c);
+    assert!(a.iter().all(|x| (-2.0..3.0).contains(x)));
+    assert!((0..100).all(|_| rng1.below(7) < 7));
+}
+
+#[cfg(test)]
+#[test]
+fn test_sobol_stratification() {
+    let n = 1 << 8;
+    let points = sobol(n, Sobol::MAX_DIMENSIONS).unwrap();
+
+    // Every dimension puts exactly one point into each of the n intervals.
+    for d in 0..Sobol::MAX_DIMENSIONS {
+        let mut bins = vec![0; n];
+        for p in &points {
+            bins[(p[d] * n as f64) as usize] += 1;
+        }
+        assert!(bins.iter().all(|&b| b == 1), "dimension {d}");
+    }
+
+    // The first two dimensions form a (0, m, 2)-net: one point in each 16x16 square.
+    let mut squares = vec![0; n];
+    for p in &points {
+        squares[(p[0] * 16.0) as usize * 16 + (p[1] * 16.0) as usize] += 1;
+    }
+    assert!(squares.iter().all(|&b| b == 1));
+
+    assert!(Sobol::new(Sobol::MAX_DIMENSIONS + 1).is_none());
+}
+
+#[cfg(test)]
+#[test]
+fn test_halton_and_lhs() {
+    let points = halton(1000, 3, 0);
+    for d in 0..3 {
+        let mean = points.iter().map(|p| p[d]).sum::<f64>() / 1000.0;
+        assert_float_absolute_eq!(mean, 0.5, 1.0e-2);
+    }
+
+    let mut rng = SplitMix64::seed_from_u64(9);
+    let mut points = latin_hypercube(20, 4, &mut rng);
+    for d in 0..4 {
+        let mut strata: Vec<usize> = points.iter().map(|p| (p[d] * 20.0) as usize).collect();
+        strata.sort();
+        assert_eq!(strata, (0..20).collect::<Vec<usize>>());
+    }
+
+    scale_to_box(&mut points, &[-1.0; 4], &[3.0; 4]);
+    assert!(points.iter().flatten().all(|&x| (-1.0..3.0).contains(&x)));
+}
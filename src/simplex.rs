@@ -8,55 +8,8 @@
 //! 1. William H. Press - Numerical recipes, the art of scientific computing.
 //!   Cambridge University Press (2007).
 //!
-
-struct Matrix {
-    pub nrows: usize,
-    pub ncols: usize,
-    pub v: Vec<f64>,
-}
-
-impl Matrix {
-    pub fn new(nrows: usize, ncols: usize) -> Self {
-        let mut m = Matrix {
-            nrows,
-            ncols,
-            v : Vec::<f64>::with_capacity(nrows * ncols),
-        };
-        m.v.resize(nrows * ncols, 0.0);
-        m
-    }
-
-    #[inline] pub fn vpos(&self, row: usize, col: usize) -> usize {
-        row*self.ncols + col
-    }
-
-    #[inline] pub fn get(&self, row: usize, col: usize) -> f64 {
-        self.v[self.vpos(row, col)]
-    }
-
-    #[inline] pub fn set(&mut self, row: usize, col: usize, new_val: f64) -> &mut Self {
-        let vpos = self.vpos(row, col);
-        self.v[vpos] = new_val;
-        self
-    }
-
-    #[inline] pub fn swap(&mut self, row1: usize, col1: usize, row2: usize, col2: usize) {
-        let vpos1 = self.vpos(row1, col1);
-        let vpos2 = self.vpos(row2, col2);
-        self.v.swap(vpos1, vpos2);
-    }
-
-    #[inline] pub fn get_psum(&self, psum: &mut [f64]) {
-        #[allow(clippy::needless_range_loop)]
-        for j in 0..self.ncols {
-            let mut sum = 0.0;
-            for i in 0..self.nrows {
-                sum += self.get(i, j);
-            }
-            psum[j] = sum;
-        }
-    }
-}
+use super::linalg::Matrix;
+use super::bounds::{ActiveBound, project, active_bounds};
 
 /// Downhill Simplex Method in Multidimensions.
 ///
@@ -100,6 +53,104 @@ pub fn amoeba<F: Fn (&[f64]) -> f64>(
     ftol: f64,
     max_iterations: usize
 ) -> (Vec<f64>, f64, usize)
+{
+    let res = amoeba_impl(fun, point, step_delta, ftol, max_iterations, None);
+    (res.xmin, res.fmin, res.nr_iterations)
+}
+
+/// Result of the downhill simplex search with the final simplex.
+pub struct AmoebaSimplexRes {
+    /// Location of the minimum.
+    pub xmin: Vec<f64>,
+    /// f(xmin)
+    pub fmin: f64,
+    /// Number of iterations.
+    pub nr_iterations: usize,
+    /// Vertices of the final simplex, `ndim + 1` points with `xmin` first.
+    pub simplex: Vec<Vec<f64>>,
+    /// Function values at the vertices of the final simplex.
+    pub values: Vec<f64>,
+}
+
+/// Downhill Simplex Method that also returns the final simplex.
+///
+/// Same as [`amoeba`]. The final simplex surrounds the minimum and, together with the
+/// function values at its vertices, can be reused to estimate the curvature there, see
+/// [`hessian_from_simplex`](crate::hessian_from_simplex).
+pub fn amoeba_simplex<F: Fn (&[f64]) -> f64>(
+    fun: F,
+    point: &[f64],
+    step_delta: f64,
+    ftol: f64,
+    max_iterations: usize
+) -> AmoebaSimplexRes
+{
+    amoeba_impl(fun, point, step_delta, ftol, max_iterations, None)
+}
+
+/// Result of the bounded downhill simplex search.
+pub struct AmoebaBoundedRes {
+    /// Location of the minimum.
+    pub xmin: Vec<f64>,
+    /// f(xmin)
+    pub fmin: f64,
+    /// Number of iterations, including the restart.
+    pub nr_iterations: usize,
+    /// Bounds active at `xmin`.
+    pub active: Vec<ActiveBound>,
+}
+
+/// Downhill Simplex Method with box constraints `lower[i] <= x[i] <= upper[i]`.
+///
+/// Same as [`amoeba`], but every trial point is projected onto the box, so the function
+/// is never evaluated outside of it. The initial simplex is made feasible by projecting
+/// `point` and stepping away from a bound instead of across it. Projection can flatten
+/// the simplex against a face of the box, so as recommended by Numerical Recipes
+/// the search is restarted once from the minimum it found.
+///
+/// # Example
+///
+/// ```
+/// use rustamath_mnmz::amoeba_bounded;
+/// use rustamath_mnmz::bounds::ActiveBound;
+/// use assert_float_eq::*;
+/// // Unconstrained minimum at (1, 2) is outside of the box [-1, 0.5] x [-1, 3].
+/// let paraboloid = |x: &[f64]| 10.0*(x[0] - 1.0)*(x[0] - 1.0) + 20.0*(x[1] - 2.0)*(x[1] - 2.0);
+///
+/// let res = amoeba_bounded(paraboloid, &[0.0, 0.0], &[-1.0, -1.0], &[0.5, 3.0], 0.5, 1.0e-10, 500);
+///
+/// assert_float_absolute_eq!(res.xmin[0], 0.5, 1.0e-8);
+/// assert_float_absolute_eq!(res.xmin[1], 2.0, 1.0e-4);
+/// assert_eq!(res.active, vec![ActiveBound::Upper, ActiveBound::Free]);
+/// ```
+pub fn amoeba_bounded<F: Fn (&[f64]) -> f64>(
+    fun: F,
+    point: &[f64],
+    lower: &[f64],
+    upper: &[f64],
+    step_delta: f64,
+    ftol: f64,
+    max_iterations: usize
+) -> AmoebaBoundedRes
+{
+    let bounds = Some((lower, upper));
+    let first = amoeba_impl(&fun, point, step_delta, ftol, max_iterations, bounds);
+    let AmoebaSimplexRes { xmin, fmin, nr_iterations, .. } =
+        amoeba_impl(&fun, &first.xmin, step_delta, ftol, max_iterations, bounds);
+
+    let active = active_bounds(&xmin, lower, upper);
+
+    AmoebaBoundedRes { xmin, fmin, nr_iterations: first.nr_iterations + nr_iterations, active }
+}
+
+fn amoeba_impl<F: Fn (&[f64]) -> f64>(
+    fun: F,
+    point: &[f64],
+    step_delta: f64,
+    ftol: f64,
+    max_iterations: usize,
+    bounds: Option<(&[f64], &[f64])>
+) -> AmoebaSimplexRes
 {
     const MIN_TOLERANCE: f64 = 1.0e-10_f64; // can be as small as f64 precision
     let ftol = ftol.max(MIN_TOLERANCE);
@@ -110,6 +161,25 @@ pub fn amoeba<F: Fn (&[f64]) -> f64>(
 
     const TINY: f64 = 1.0e-10;
 
+    let mut point = point.to_vec();
+    if let Some((lower, upper)) = bounds {
+        project(&mut point, lower, upper);
+        // Step away from a bound instead of across it, and never further
+        // than the box is wide. The loop below moves vertex j+1 along
+        // coordinate j by ndim-j times dels[j], so size that displacement.
+        let inside = |x: f64, j: usize| x >= lower[j] && x <= upper[j];
+        for j in 0..ndim {
+            let nr_steps = (ndim - j) as f64;
+            let step = nr_steps * dels[j];
+            if !inside(point[j] + step, j) {
+                let step = if inside(point[j] - step, j) { -step }
+                    else if upper[j] - point[j] >= point[j] - lower[j] { upper[j] - point[j] }
+                    else { lower[j] - point[j] };
+                dels[j] = step / nr_steps;
+            }
+        }
+    }
+
     let mut p = Matrix::new(ndim+1, ndim);
     #[allow(clippy::needless_range_loop)]
     for i in 0..ndim+1 {
@@ -117,6 +187,10 @@ pub fn amoeba<F: Fn (&[f64]) -> f64>(
             p.set(i, j, point[j]);
             if i != 0 { let x = p.get(i, i-1) + dels[i-1]; p.set(i, i-1, x); }
         }
+        // Guard against rounding past the bound.
+        if let Some((lower, upper)) = bounds {
+            if i != 0 { p.set(i, i-1, p.get(i, i-1).max(lower[i-1]).min(upper[i-1])); }
+        }
     }
 
     let mpts = ndim + 1;
@@ -188,18 +262,18 @@ pub fn amoeba<F: Fn (&[f64]) -> f64>(
 
         // Begin a new iteration. First extrapolate by a factor -1 through the face of the
         // simplex across from the high point, i.e., reflect the simplex from the high point.
-        let mut ytry = amoeba_try(&mut p, &mut y, &mut psum, ihi, -1.0, &fun, &mut ptry);
+        let mut ytry = amoeba_try(&mut p, &mut y, &mut psum, ihi, -1.0, &fun, &mut ptry, bounds);
 
         if ytry <= y[ilo] {
             // Gives a result better than the best point, so try an additional extrapolation
             // by a factor 2.
-            /*ytry =*/ amoeba_try(&mut p, &mut y, &mut psum, ihi, 2.0, &fun, &mut ptry);
+            /*ytry =*/ amoeba_try(&mut p, &mut y, &mut psum, ihi, 2.0, &fun, &mut ptry, bounds);
         }
         else if ytry >= y[inhi] {
             // The reflected point is worse than the second-highest, so look for an intermediate
             // lower point, i.e., do a one-dimensional contraction.
             let ysave = y[ihi];
-            ytry = amoeba_try(&mut p, &mut y, &mut psum, ihi, 0.5, &fun, &mut ptry);
+            ytry = amoeba_try(&mut p, &mut y, &mut psum, ihi, 0.5, &fun, &mut ptry, bounds);
             if ytry >= ysave {
                 // Can’t seem to get rid of that high point.
                 // Better contract around the lowest (best) point.
@@ -224,11 +298,15 @@ pub fn amoeba<F: Fn (&[f64]) -> f64>(
         nr_iterations += 1;
     }
 
-    (pmin, fmin, nr_iterations)
+    let simplex = (0..mpts).map(|i| (0..ndim).map(|j| p.get(i, j)).collect()).collect();
+
+    AmoebaSimplexRes { xmin: pmin, fmin, nr_iterations, simplex, values: y }
 }
 
 // Helper function: Extrapolates by a factor fac through the face of the simplex across from
 // the high point, tries it, and replaces the high point if the new point is better.
+// With bounds the trial point is projected onto the box first.
+#[allow(clippy::too_many_arguments)]
 fn amoeba_try<F: Fn (&[f64]) -> f64>(
     p: &mut Matrix,
     y: &mut [f64],
@@ -236,7 +314,8 @@ fn amoeba_try<F: Fn (&[f64]) -> f64>(
     ihi: usize,
     fac: f64,
     fun: F,
-    ptry: &mut [f64] // size ndim
+    ptry: &mut [f64], // size ndim
+    bounds: Option<(&[f64], &[f64])>
 ) -> f64
 {
     let ndim = p.ncols;
@@ -248,6 +327,10 @@ fn amoeba_try<F: Fn (&[f64]) -> f64>(
         ptry[j] = psum[j] * fac1 - p.get(ihi, j) * fac2;
     }
 
+    if let Some((lower, upper)) = bounds {
+        project(ptry, lower, upper);
+    }
+
     let ytry = fun(ptry); // Evaluate the function at the trial point.
 
     // If it’s better than the highest, then replace the highest.
@@ -298,6 +381,7 @@ fn test_x2_y2_xy() {
 //https://www.gnu.org/software/gsl/doc/html/multimin.html
 #[cfg(test)]
 #[test]
+#[allow(clippy::useless_vec)]
 fn test_paraboloid() {
     //  Paraboloid center at (1,2), scale factors (10, 20), minimum value 30
     let p = vec![1.0, 2.0, 10.0, 20.0, 30.0];
@@ -314,4 +398,105 @@ fn test_paraboloid() {
     assert_float_absolute_eq!(min[0], 1.0, 1.0e-4);
     assert_float_absolute_eq!(min[1], 2.0, 1.0e-4);
     assert_float_absolute_eq!(fmin,  30.0, 1.0e-4);
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+#[test]
+fn test_bounded_paraboloid() {
+    use super::bounds::ActiveBound;
+
+    let paraboloid = |x: &[f64]| 10.0*(x[0] - 1.0)*(x[0] - 1.0) + 20.0*(x[1] - 2.0)*(x[1] - 2.0) + 30.0;
+
+    // Box containing the minimum: same answer as without bounds.
+    let res = amoeba_bounded(paraboloid, &[4.0, -4.0], &[-5.0, -5.0], &[5.0, 5.0], 1.1, 1.0e-10, 500);
+    assert_float_absolute_eq!(res.xmin[0], 1.0, 1.0e-4);
+    assert_float_absolute_eq!(res.xmin[1], 2.0, 1.0e-4);
+    assert_eq!(res.active, vec![ActiveBound::Free, ActiveBound::Free]);
+
+    // Minimum in the corner of the box, the start point on the upper bound.
+    let res = amoeba_bounded(paraboloid, &[-1.0, 0.0], &[-3.0, -3.0], &[-1.0, 0.0], 1.1, 1.0e-10, 500);
+
+    println!("min: {}, {} fmin: {} iterations: {} active: {:?}",
+        res.xmin[0], res.xmin[1], res.fmin, res.nr_iterations, res.active);
+
+    assert_float_absolute_eq!(res.xmin[0], -1.0, 1.0e-10);
+    assert_float_absolute_eq!(res.xmin[1], 0.0, 1.0e-10);
+    assert_float_absolute_eq!(res.fmin, 150.0, 1.0e-8);
+    assert_eq!(res.active, vec![ActiveBound::Upper, ActiveBound::Upper]);
+}
+
+#[cfg(test)]
+#[test]
+fn test_bounded_feasible_evaluations() {
+    use std::cell::Cell;
+
+    // Rosenbrock function with the minimum (1, 1) cut off by the box, x[1] <= 0.5.
+    let outside = Cell::new(0);
+    let lower = [-2.0, -2.0];
+    let upper = [2.0, 0.5];
+    let rosenbrock = |x: &[f64]| {
+        if x.iter().zip(lower.iter().zip(upper.iter())).any(|(xi, (lo, hi))| xi < lo || xi > hi) {
+            outside.set(outside.get() + 1);
+        }
+        100.0*(x[1] - x[0]*x[0]).powi(2) + (1.0 - x[0]).powi(2)
+    };
+
+    let res = amoeba_bounded(rosenbrock, &[1.9, 0.45], &lower, &upper, 1.0, 1.0e-12, 2000);
+
+    println!("min: {}, {} fmin: {} iterations: {} active: {:?}",
+        res.xmin[0], res.xmin[1], res.fmin, res.nr_iterations, res.active);
+
+    assert_eq!(outside.get(), 0);
+    assert_float_absolute_eq!(res.xmin[1], 0.5, 1.0e-8);
+    assert_float_absolute_eq!(res.xmin[0], 0.7086, 1.0e-3);
+    assert_eq!(res.active[1], ActiveBound::Upper);
+}
+
+#[cfg(test)]
+#[test]
+fn test_bounded_negative_step() {
+    use std::cell::Cell;
+
+    // Negative step from a start point next to the lower bound.
+    let outside = Cell::new(0);
+    let lower = [0.0, 0.0];
+    let upper = [3.0, 3.0];
+    let paraboloid = |x: &[f64]| {
+        if x.iter().zip(lower.iter().zip(upper.iter())).any(|(xi, (lo, hi))| xi < lo || xi > hi) {
+            outside.set(outside.get() + 1);
+        }
+        10.0*(x[0] - 1.0)*(x[0] - 1.0) + 20.0*(x[1] - 2.0)*(x[1] - 2.0)
+    };
+
+    let res = amoeba_bounded(paraboloid, &[0.1, 0.2], &lower, &upper, -0.5, 1.0e-10, 500);
+
+    println!("min: {}, {} fmin: {} iterations: {} active: {:?}",
+        res.xmin[0], res.xmin[1], res.fmin, res.nr_iterations, res.active);
+
+    assert_eq!(outside.get(), 0);
+    assert_float_absolute_eq!(res.xmin[0], 1.0, 1.0e-4);
+    assert_float_absolute_eq!(res.xmin[1], 2.0, 1.0e-4);
+}
+
+#[cfg(test)]
+#[test]
+fn test_bounded_initial_simplex() {
+    use std::cell::RefCell;
+
+    // The first step along x[0] does not fit below the upper bound, so it is taken downwards.
+    let evaluated = RefCell::new(Vec::new());
+    let lower = [-2.0, -2.0, -2.0];
+    let upper = [1.0, 1.0, 1.0];
+    let sphere = |x: &[f64]| {
+        evaluated.borrow_mut().push(x.to_vec());
+        x.iter().map(|xi| (xi - 0.5) * (xi - 0.5)).sum::<f64>()
+    };
+
+    let res = amoeba_bounded(sphere, &[0.0, 0.0, 0.0], &lower, &upper, 0.5, 1.0e-10, 500);
+
+    let initial = &evaluated.borrow()[..4];
+    println!("initial simplex: {:?}", initial);
+    assert!(initial.iter().all(|x| x.iter().all(|&xi| (-2.0..=1.0).contains(&xi))));
+    assert_float_absolute_eq!(initial[1][0], -1.5, 1.0e-15);
+    assert_float_absolute_eq!(res.xmin[0], 0.5, 1.0e-4);
+}
@@ -0,0 +1,206 @@
+//! Sparse Jacobian by finite differences with column coloring.
+//!
+//! (c) 2023 Igor Lesik
+//! MIT license
+//!
+//! References:
+//!
+//! 1. A. R. Curtis, M. J. D. Powell, J. K. Reid - On the estimation of sparse Jacobian
+//!    matrices. IMA Journal of Applied Mathematics 13 (1974).
+//! 2. Thomas F. Coleman, Jorge J. Moré - Estimation of sparse Jacobian matrices and graph
+//!    coloring problems. SIAM Journal on Numerical Analysis 20 (1983).
+//!
+
+/// Sparse Jacobian estimated by finite differences.
+pub struct SparseJacobianRes {
+    /// Nonzero elements, `values[i][k]` is `J[i][pattern[i][k]]`.
+    pub values: Vec<Vec<f64>>,
+    /// Color of every column; columns of the same color are differenced together.
+    pub colors: Vec<usize>,
+    /// Number of colors.
+    pub nr_colors: usize,
+    /// Number of function evaluations, `nr_colors + 1`.
+    pub nr_evaluations: usize,
+}
+
+/// Curtis-Powell-Reid column coloring of a sparsity pattern.
+///
+/// - A. R. Curtis, M. J. D. Powell, J. K. Reid - On the estimation of sparse Jacobian
+///   matrices (1974).
+///
+/// `pattern[i]` lists the columns of the possibly nonzero elements in row `i` of an
+/// `m x ncols` matrix. Columns are taken in order and each gets the smallest color not
+/// used by a column sharing a row with it, so no two columns of a color overlap. A banded
+/// matrix of bandwidth `w` needs `w` colors whatever the number of columns.
+///
+/// Returns the color of every column.
+///
+/// # Example
+///
+/// ```
+/// use rustamath_mnmz::color_columns;
+/// // Tridiagonal 5 x 5.
+/// let pattern: Vec<Vec<usize>> = (0..5).map(|i: usize| (i.saturating_sub(1)..(i + 2).min(5)).collect()).collect();
+///
+/// assert_eq!(color_columns(&pattern, 5), vec![0, 1, 2, 0, 1]);
+/// ```
+pub fn color_columns(pattern: &[Vec<usize>], ncols: usize) -> Vec<usize> {
+    // Rows of every column.
+    let mut col_rows = vec![Vec::<usize>::new(); ncols];
+    for (i, row) in pattern.iter().enumerate() {
+        for &j in row.iter() {
+            col_rows[j].push(i);
+        }
+    }
+
+    const NONE: usize = usize::MAX;
+    let mut colors = vec![NONE; ncols];
+    // Last column that marked a color as taken, reset-free marking.
+    let mut taken_by = Vec::<usize>::new();
+    for j in 0..ncols {
+        for &i in col_rows[j].iter() {
+            for &k in pattern[i].iter() {
+                if colors[k] != NONE {
+                    taken_by[colors[k]] = j;
+                }
+            }
+        }
+        let color = (0..taken_by.len()).find(|&c| taken_by[c] != j).unwrap_or(taken_by.len());
+        if color == taken_by.len() {
+            taken_by.push(NONE);
+        }
+        colors[j] = color;
+    }
+    colors
+}
+
+/// Sparse Jacobian of `fun` at `x` by forward differences over colored columns.
+///
+/// - A. R. Curtis, M. J. D. Powell, J. K. Reid - On the estimation of sparse Jacobian
+///   matrices (1974).
+///
+/// `fun(x, r)` stores the `m` residuals at `x` in `r`, and `pattern[i]` lists the columns
+/// where row `i` of the Jacobian may be nonzero. The columns are colored by
+/// [`color_columns`]. All columns of one color are perturbed at once, each by its own step
+/// `h_j = noise^(1/2) max(|x_j|, 1)`; since they share no row, every changed residual
+/// belongs to exactly one of them. The Jacobian costs one evaluation per color plus one
+/// at `x`, instead of one per column. `noise` is the relative error of `fun`, at least
+/// `f64::EPSILON` (used if 0).
+///
+/// # Example
+///
+/// ```
+/// use rustamath_mnmz::sparse_jacobian;
+/// use assert_float_eq::*;
+/// // r_i = x_i^2 - x_{i+1}, bidiagonal Jacobian.
+/// let n = 1000;
+/// let fun = |x: &[f64], r: &mut [f64]| {
+///     for i in 0..n - 1 {
+///         r[i] = x[i] * x[i] - x[i + 1];
+///     }
+/// };
+/// let pattern: Vec<Vec<usize>> = (0..n - 1).map(|i| vec![i, i + 1]).collect();
+///
+/// let res = sparse_jacobian(fun, &vec![2.0; n], n - 1, &pattern, 0.0);
+///
+/// assert_eq!(res.nr_evaluations, 3);
+/// assert_float_absolute_eq!(res.values[10][0], 4.0, 1.0e-6);
+/// assert_float_absolute_eq!(res.values[10][1], -1.0, 1.0e-6);
+/// ```
+pub fn sparse_jacobian<F: Fn (&[f64], &mut [f64])>(
+    fun: F,
+    x: &[f64],
+    m: usize,
+    pattern: &[Vec<usize>],
+    noise: f64
+) -> SparseJacobianRes
+{
+    let n = x.len();
+    let noise = noise.max(f64::EPSILON);
+    let colors = color_columns(pattern, n);
+    let nr_colors = colors.iter().map(|&c| c + 1).max().unwrap_or(0);
+
+    // Steps exactly representable as the difference of two floating point numbers.
+    let h: Vec<f64> = x.iter().map(|&xj| (xj + noise.sqrt() * xj.abs().max(1.0)) - xj).collect();
+
+    let mut r0 = vec![0.0; m];
+    fun(x, &mut r0);
+    let mut r = vec![0.0; m];
+    let mut xt = x.to_vec();
+    let mut values: Vec<Vec<f64>> = pattern.iter().map(|row| vec![0.0; row.len()]).collect();
+
+    for color in 0..nr_colors {
+        for j in 0..n {
+            if colors[j] == color {
+                xt[j] = x[j] + h[j];
+            }
+        }
+        fun(&xt, &mut r);
+        for (i, row) in pattern.iter().enumerate() {
+            for (k, &j) in row.iter().enumerate() {
+                if colors[j] == color {
+                    values[i][k] = (r[i] - r0[i]) / h[j];
+                }
+            }
+        }
+        xt.copy_from_slice(x);
+    }
+
+    SparseJacobianRes { values, colors, nr_colors, nr_evaluations: nr_colors + 1 }
+}
+
+#[cfg(test)]
+#[test]
+fn test_coloring_is_valid() {
+    use super::sampling::{Rng, SeedableRng, SplitMix64};
+
+    // Random sparse pattern with 3 nonzeros per row.
+    let (m, n) = (300, 200);
+    let mut rng = SplitMix64::seed_from_u64(11);
+    let pattern: Vec<Vec<usize>> = (0..m).map(|_| {
+        let mut row: Vec<usize> = (0..3).map(|_| rng.below(n)).collect();
+        row.sort();
+        row.dedup();
+        row
+    }).collect();
+
+    let colors = color_columns(&pattern, n);
+    let nr_colors = colors.iter().max().unwrap() + 1;
+    println!("{} colors for {} columns", nr_colors, n);
+    assert!(nr_colors < n / 4);
+    for row in pattern.iter() {
+        for (a, &j) in row.iter().enumerate() {
+            for &k in row[a + 1..].iter() {
+                assert_ne!(colors[j], colors[k]);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_broyden_tridiagonal() {
+    // Broyden tridiagonal function, r_i = (3 - 2 x_i) x_i - x_{i-1} - 2 x_{i+1} + 1.
+    let n = 2000;
+    let fun = |x: &[f64], r: &mut [f64]| {
+        for i in 0..n {
+            let prev = if i > 0 { x[i - 1] } else { 0.0 };
+            let next = if i + 1 < n { x[i + 1] } else { 0.0 };
+            r[i] = (3.0 - 2.0 * x[i]) * x[i] - prev - 2.0 * next + 1.0;
+        }
+    };
+    let pattern: Vec<Vec<usize>> = (0..n).map(|i: usize| (i.saturating_sub(1)..(i + 2).min(n)).collect()).collect();
+    let x: Vec<f64> = (0..n).map(|i| -1.0 + (i as f64 * 0.37).sin()).collect();
+
+    let res = sparse_jacobian(fun, &x, n, &pattern, 0.0);
+    println!("colors: {} evaluations: {}", res.nr_colors, res.nr_evaluations);
+    assert_eq!(res.nr_colors, 3);
+    assert_eq!(res.nr_evaluations, 4);
+
+    for i in 0..n {
+        for (k, &j) in pattern[i].iter().enumerate() {
+            let exact = if j + 1 == i { -1.0 } else if j == i { 3.0 - 4.0 * x[i] } else { -2.0 };
+            assert_float_absolute_eq!(res.values[i][k], exact, 1.0e-6);
+        }
+    }
+}
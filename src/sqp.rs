@@ -0,0 +1,376 @@
+//! Sequential quadratic programming for nonlinear constraints.
+//!
+//! (c) 2023 Igor Lesik
+//! MIT license
+//!
+//! References:
+//!
+//! 1. M. J. D. Powell - A fast algorithm for nonlinearly constrained optimization
+//!    calculations. Numerical Analysis, Lecture Notes in Mathematics 630 (1978).
+//! 2. Dieter Kraft - A software package for sequential quadratic programming.
+//!    DFVLR-FB 88-28 (1988).
+//! 3. Jorge Nocedal, Stephen J. Wright - Numerical Optimization. Springer (2006).
+//!    Chapter 18.
+//!
+use super::qp::{quadprog, QpStatus};
+
+/// Sufficient decrease parameter of the merit function line search.
+const ARMIJO: f64 = 1.0e-4_f64;
+
+/// Smallest allowed tolerance.
+const MIN_TOLERANCE: f64 = 1.0e-12_f64;
+
+/// Penalty on the relaxation of an inconsistent QP subproblem.
+const ELASTIC_PENALTY: f64 = 1.0e4_f64;
+
+/// Result of sequential quadratic programming.
+pub struct SqpRes {
+    /// Location of the constrained minimum.
+    pub xmin: Vec<f64>,
+    /// Objective value at `xmin`.
+    pub fmin: f64,
+    /// Largest constraint violation `max(|h_i(x)|, g_j(x))` at `xmin`, 0 if feasible.
+    pub violation: f64,
+    /// Multiplier estimates of the equality constraints.
+    pub lambda_eq: Vec<f64>,
+    /// Multiplier estimates of the inequality constraints, all `>= 0`.
+    pub lambda_ineq: Vec<f64>,
+    /// Whether the step and the violation fell below the tolerance.
+    pub converged: bool,
+    /// Number of iterations.
+    pub nr_iterations: usize,
+    /// Number of evaluations of the objective and constraints.
+    pub nr_evaluations: usize,
+}
+
+/// Values and derivatives of the objective and constraints at a point.
+struct Point {
+    x: Vec<f64>,
+    f: f64,
+    grad: Vec<f64>,
+    h: Vec<f64>,
+    jh: Vec<Vec<f64>>,
+    g: Vec<f64>,
+    jg: Vec<Vec<f64>>,
+}
+
+impl Point {
+    /// l1 norm of the constraint violation.
+    fn infeasibility(&self) -> f64 {
+        self.h.iter().map(|hi| hi.abs()).sum::<f64>() + self.g.iter().map(|gj| gj.max(0.0)).sum::<f64>()
+    }
+
+    /// Gradient of the Lagrangian `f + lambda^T h + mu^T g`.
+    fn lagrangian_grad(&self, lambda: &[f64], mu: &[f64]) -> Vec<f64> {
+        let mut gl = self.grad.clone();
+        for (row, l) in self.jh.iter().zip(lambda.iter()).chain(self.jg.iter().zip(mu.iter())) {
+            for (gi, ri) in gl.iter_mut().zip(row.iter()) {
+                *gi += l * ri;
+            }
+        }
+        gl
+    }
+}
+
+/// Sequential quadratic programming (SLSQP-style).
+///
+/// - M. J. D. Powell - A fast algorithm for nonlinearly constrained optimization
+///   calculations (1978).
+/// - Dieter Kraft - A software package for sequential quadratic programming (1988).
+///
+/// Minimizes `f(x)` subject to `h(x) = 0` and `g(x) <= 0` for smooth functions with
+/// derivatives. `fun(x, grad)` returns `f(x)` and stores its gradient; the constraint
+/// callbacks `eq(x, h, jac)` and `ineq(x, g, jac)` store the `m_eq` and `m_ineq` constraint
+/// values and the rows of their Jacobians. Pass `|_, _, _| {}` with a count of 0 when there
+/// are no constraints of a kind.
+///
+/// Each iteration solves the QP subproblem
+///
+/// ```text
+/// minimize grad^T d + d^T B d / 2  subject to  h + J_h d = 0,  g + J_g d <= 0
+/// ```
+///
+/// with [`quadprog`](crate::quadprog); when the linearized constraints are inconsistent they
+/// are relaxed as in Powell's method. The step is shortened by a backtracking line search
+/// on the l1 merit function `f + nu (sum |h_i| + sum max(0, g_j))`, with the penalty `nu`
+/// kept above the QP multipliers. `B` approximates the Hessian of the Lagrangian by the
+/// damped BFGS update, which keeps it positive definite.
+///
+/// The multipliers follow the sign convention `grad f + J_h^T lambda + J_g^T mu = 0`.
+///
+/// Stops when the step and the violation are below `tol`, or after `max_iterations`
+/// iterations (100 if 0).
+///
+/// # Example
+///
+/// ```
+/// use rustamath_mnmz::sqp;
+/// use assert_float_eq::*;
+/// // minimize x + y  subject to  x^2 + y^2 = 2
+/// let res = sqp(
+///     |x: &[f64], grad: &mut [f64]| { grad[0] = 1.0; grad[1] = 1.0; x[0] + x[1] },
+///     |x: &[f64], h: &mut [f64], jac: &mut [Vec<f64>]| {
+///         h[0] = x[0]*x[0] + x[1]*x[1] - 2.0;
+///         jac[0][0] = 2.0*x[0];
+///         jac[0][1] = 2.0*x[1];
+///     }, 1,
+///     |_: &[f64], _: &mut [f64], _: &mut [Vec<f64>]| {}, 0,
+///     &[0.5, -0.2], 1.0e-10, 0);
+///
+/// assert!(res.converged);
+/// assert_float_absolute_eq!(res.xmin[0], -1.0, 1.0e-9);
+/// assert_float_absolute_eq!(res.xmin[1], -1.0, 1.0e-9);
+/// assert_float_absolute_eq!(res.lambda_eq[0], 0.5, 1.0e-9);
+/// ```
+#[allow(clippy::too_many_arguments)]
+#[allow(clippy::needless_range_loop)]
+pub fn sqp<F, E, G>(
+    fun: F,
+    eq: E,
+    m_eq: usize,
+    ineq: G,
+    m_ineq: usize,
+    point: &[f64],
+    tol: f64,
+    max_iterations: usize
+) -> SqpRes
+where
+    F: Fn (&[f64], &mut [f64]) -> f64,
+    E: Fn (&[f64], &mut [f64], &mut [Vec<f64>]),
+    G: Fn (&[f64], &mut [f64], &mut [Vec<f64>])
+{
+    let tol = tol.max(MIN_TOLERANCE);
+    let max_iterations = if max_iterations < 1 { 100 } else { max_iterations };
+    let n = point.len();
+
+    let dot = |a: &[f64], b: &[f64]| a.iter().zip(b.iter()).map(|(x, y)| x * y).sum::<f64>();
+    let evaluate = |x: Vec<f64>, nr_evaluations: &mut usize| {
+        *nr_evaluations += 1;
+        let mut grad = vec![0.0; n];
+        let f = fun(&x, &mut grad);
+        let mut h = vec![0.0; m_eq];
+        let mut jh = vec![vec![0.0; n]; m_eq];
+        eq(&x, &mut h, &mut jh);
+        let mut g = vec![0.0; m_ineq];
+        let mut jg = vec![vec![0.0; n]; m_ineq];
+        ineq(&x, &mut g, &mut jg);
+        Point { x, f, grad, h, jh, g, jg }
+    };
+    let identity = || -> Vec<Vec<f64>> {
+        (0..n).map(|i| (0..n).map(|j| if i == j { 1.0 } else { 0.0 }).collect()).collect()
+    };
+
+    let mut nr_evaluations: usize = 0;
+    let mut p = evaluate(point.to_vec(), &mut nr_evaluations);
+    let mut b = identity();
+    let mut lambda = vec![0.0; m_eq];
+    let mut mu = vec![0.0; m_ineq];
+    let mut nu: f64 = 0.0;
+    let mut converged = false;
+    let mut nr_iterations: usize = 0;
+
+    while nr_iterations < max_iterations {
+        nr_iterations += 1;
+
+        let neg_h: Vec<f64> = p.h.iter().map(|hi| -hi).collect();
+        let neg_g: Vec<f64> = p.g.iter().map(|gj| -gj).collect();
+        let qp = quadprog(&b, &p.grad, &p.jg, &neg_g, &p.jh, &neg_h, 0);
+
+        // Direction, its QP multipliers and the fraction of the violation it removes.
+        let (d, qp_lambda, qp_mu, xi) = if qp.status == QpStatus::Optimal {
+            (qp.x, qp.lambda_eq, qp.lambda_ub, 1.0)
+        }
+        else {
+            // Inconsistent linearization: scale the violated constraints by xi in [0, 1],
+            // J_h d + xi h = 0 and J_g d + xi g <= 0, penalizing (1 - xi)^2.
+            let mut q = b.clone();
+            q.iter_mut().for_each(|row| row.push(0.0));
+            let mut last = vec![0.0; n + 1];
+            last[n] = ELASTIC_PENALTY;
+            q.push(last);
+            let mut c = p.grad.clone();
+            c.push(-ELASTIC_PENALTY);
+            let a_eq: Vec<Vec<f64>> = (0..m_eq)
+                .map(|i| p.jh[i].iter().copied().chain(std::iter::once(p.h[i])).collect())
+                .collect();
+            let mut a_ub: Vec<Vec<f64>> = Vec::with_capacity(m_ineq + 2);
+            let mut b_ub: Vec<f64> = Vec::with_capacity(m_ineq + 2);
+            for j in 0..m_ineq {
+                let violated = p.g[j] > 0.0;
+                a_ub.push(p.jg[j].iter().copied().chain(std::iter::once(if violated { p.g[j] } else { 0.0 })).collect());
+                b_ub.push(if violated { 0.0 } else { -p.g[j] });
+            }
+            let mut row = vec![0.0; n + 1];
+            row[n] = 1.0;
+            a_ub.push(row.clone());
+            b_ub.push(1.0);
+            row[n] = -1.0;
+            a_ub.push(row);
+            b_ub.push(0.0);
+
+            let qp = quadprog(&q, &c, &a_ub, &b_ub, &a_eq, &vec![0.0; m_eq], 0);
+            if qp.status != QpStatus::Optimal {
+                break;
+            }
+            let xi = qp.x[n];
+            let mut d = qp.x;
+            d.truncate(n);
+            let mut qp_mu = qp.lambda_ub;
+            qp_mu.truncate(m_ineq);
+            (d, qp.lambda_eq, qp_mu, xi)
+        };
+
+        let dnorm = d.iter().fold(0.0_f64, |s, di| s.max(di.abs()));
+        let xnorm = p.x.iter().fold(0.0_f64, |s, xi| s.max(xi.abs()));
+        let violation = p.h.iter().map(|hi| hi.abs()).chain(p.g.iter().copied()).fold(0.0_f64, f64::max);
+        if dnorm <= tol * (1.0 + xnorm) && violation <= tol {
+            lambda = qp_lambda;
+            mu = qp_mu;
+            converged = true;
+            break;
+        }
+
+        // l1 merit function and its directional derivative along d.
+        let max_multiplier = qp_lambda.iter().chain(qp_mu.iter()).fold(0.0_f64, |s, l| s.max(l.abs()));
+        nu = nu.max(1.5 * max_multiplier);
+        let merit = |p: &Point| p.f + nu * p.infeasibility();
+        let phi = merit(&p);
+        let slope = dot(&p.grad, &d) - nu * xi * p.infeasibility();
+
+        let mut step = 1.0;
+        let mut next = None;
+        while step > 1.0e-10 {
+            let x: Vec<f64> = p.x.iter().zip(d.iter()).map(|(xi, di)| xi + step * di).collect();
+            let trial = evaluate(x, &mut nr_evaluations);
+            if merit(&trial) <= phi + ARMIJO * step * slope.min(0.0) {
+                next = Some(trial);
+                break;
+            }
+            step *= 0.5;
+        }
+        let Some(next) = next else {
+            if b != identity() {
+                b = identity();
+                continue;
+            }
+            break;
+        };
+
+        // Damped BFGS update of the Lagrangian Hessian with the new multipliers.
+        let s: Vec<f64> = next.x.iter().zip(p.x.iter()).map(|(a, b)| a - b).collect();
+        let gl_new = next.lagrangian_grad(&qp_lambda, &qp_mu);
+        let gl_old = p.lagrangian_grad(&qp_lambda, &qp_mu);
+        let y: Vec<f64> = gl_new.iter().zip(gl_old.iter()).map(|(a, b)| a - b).collect();
+        let bs: Vec<f64> = b.iter().map(|row| dot(row, &s)).collect();
+        let sbs = dot(&s, &bs);
+        let sy = dot(&s, &y);
+        if sbs > 0.0 {
+            let theta = if sy >= 0.2 * sbs { 1.0 } else { 0.8 * sbs / (sbs - sy) };
+            let r: Vec<f64> = (0..n).map(|i| theta * y[i] + (1.0 - theta) * bs[i]).collect();
+            let sr = dot(&s, &r);
+            for i in 0..n {
+                for j in 0..n {
+                    b[i][j] += r[i] * r[j] / sr - bs[i] * bs[j] / sbs;
+                }
+            }
+        }
+
+        lambda = qp_lambda;
+        mu = qp_mu;
+        p = next;
+    }
+
+    let violation = p.h.iter().map(|hi| hi.abs()).chain(p.g.iter().copied()).fold(0.0_f64, f64::max);
+
+    SqpRes {
+        xmin: p.x,
+        fmin: p.f,
+        violation,
+        lambda_eq: lambda,
+        lambda_ineq: mu,
+        converged,
+        nr_iterations,
+        nr_evaluations,
+    }
+}
+
+#[cfg(test)]
+#[test]
+#[allow(clippy::needless_range_loop)]
+fn test_hs071() {
+    // Hock-Schittkowski problem 71:
+    // minimize x1 x4 (x1 + x2 + x3) + x3  subject to  x1 x2 x3 x4 >= 25,
+    // x1^2 + x2^2 + x3^2 + x4^2 = 40,  1 <= x <= 5.
+    let fun = |x: &[f64], g: &mut [f64]| {
+        g[0] = x[3] * (2.0 * x[0] + x[1] + x[2]);
+        g[1] = x[0] * x[3];
+        g[2] = x[0] * x[3] + 1.0;
+        g[3] = x[0] * (x[0] + x[1] + x[2]);
+        x[0] * x[3] * (x[0] + x[1] + x[2]) + x[2]
+    };
+    let eq = |x: &[f64], h: &mut [f64], jac: &mut [Vec<f64>]| {
+        h[0] = x.iter().map(|xi| xi * xi).sum::<f64>() - 40.0;
+        for j in 0..4 {
+            jac[0][j] = 2.0 * x[j];
+        }
+    };
+    let ineq = |x: &[f64], g: &mut [f64], jac: &mut [Vec<f64>]| {
+        g[0] = 25.0 - x[0] * x[1] * x[2] * x[3];
+        for j in 0..4 {
+            jac[0][j] = -(0..4).filter(|&k| k != j).map(|k| x[k]).product::<f64>();
+        }
+        for j in 0..4 {
+            g[1 + j] = 1.0 - x[j];
+            g[5 + j] = x[j] - 5.0;
+            for k in 0..4 {
+                jac[1 + j][k] = if j == k { -1.0 } else { 0.0 };
+                jac[5 + j][k] = if j == k { 1.0 } else { 0.0 };
+            }
+        }
+    };
+
+    let res = sqp(fun, eq, 1, ineq, 9, &[1.0, 5.0, 5.0, 1.0], 1.0e-10, 0);
+
+    println!("x: {:?} f: {} violation: {} lambda: {:?} mu: {:?} iterations: {} evaluations: {}",
+        res.xmin, res.fmin, res.violation, res.lambda_eq, res.lambda_ineq,
+        res.nr_iterations, res.nr_evaluations);
+
+    assert!(res.converged);
+    assert_float_absolute_eq!(res.fmin, 17.0140172891, 1.0e-8);
+    assert_float_absolute_eq!(res.xmin[0], 1.0, 1.0e-8);
+    assert_float_absolute_eq!(res.xmin[1], 4.7429996, 1.0e-6);
+    assert_float_absolute_eq!(res.xmin[2], 3.8211499, 1.0e-6);
+    assert_float_absolute_eq!(res.xmin[3], 1.3794082, 1.0e-6);
+    assert!(res.violation < 1.0e-9);
+    assert!(res.lambda_ineq.iter().all(|&l| l >= 0.0));
+}
+
+#[cfg(test)]
+#[test]
+fn test_inconsistent_linearization() {
+    // minimize x + y  subject to  x^2 + y^2 = 2  and  y <= x^2; at the origin the linearized
+    // equality reads 0 = 2 and the subproblem has to be relaxed. The solution is (-1, -1).
+    let res = sqp(
+        |x: &[f64], g: &mut [f64]| { g[0] = 1.0; g[1] = 1.0; x[0] + x[1] },
+        |x: &[f64], h: &mut [f64], jac: &mut [Vec<f64>]| {
+            h[0] = x[0]*x[0] + x[1]*x[1] - 2.0;
+            jac[0][0] = 2.0 * x[0];
+            jac[0][1] = 2.0 * x[1];
+        }, 1,
+        |x: &[f64], g: &mut [f64], jac: &mut [Vec<f64>]| {
+            g[0] = x[1] - x[0]*x[0];
+            jac[0][0] = -2.0 * x[0];
+            jac[0][1] = 1.0;
+        }, 1,
+        &[0.0, 0.0], 1.0e-10, 0);
+
+    println!("x: {:?} f: {} violation: {} lambda: {:?} mu: {:?} iterations: {} evaluations: {}",
+        res.xmin, res.fmin, res.violation, res.lambda_eq, res.lambda_ineq,
+        res.nr_iterations, res.nr_evaluations);
+
+    assert!(res.converged);
+    assert_float_absolute_eq!(res.xmin[0], -1.0, 1.0e-9);
+    assert_float_absolute_eq!(res.xmin[1], -1.0, 1.0e-9);
+    assert_float_absolute_eq!(res.lambda_eq[0], 0.5, 1.0e-8);
+    assert_eq!(res.lambda_ineq[0], 0.0);
+}
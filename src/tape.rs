@@ -0,0 +1,381 @@
+//! Reverse-mode automatic differentiation with a tape.
+//!
+//! (c) 2023 Igor Lesik
+//! MIT license
+//!
+//! References:
+//!
+//! 1. Andreas Griewank, Andrea Walther - Evaluating Derivatives: Principles and Techniques
+//!    of Algorithmic Differentiation. SIAM (2008).
+//!
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::ops::{Add, Sub, Mul, Div, Neg, AddAssign, SubAssign, MulAssign, DivAssign};
+use super::real::Real;
+
+/// Operation recorded on the tape: up to two arguments with the partial derivatives
+/// of the result with respect to them.
+#[derive(Clone, Copy)]
+struct Node {
+    args: [(usize, f64); 2],
+}
+
+/// Record of the operations of one function evaluation.
+///
+/// Every operation on [`Var`]s is appended to the tape. A single backward pass over
+/// the tape accumulates the derivatives of the result with respect to all variables,
+/// so a gradient costs a small multiple of one function evaluation whatever the dimension.
+///
+/// # Example
+///
+/// ```
+/// use rustamath_mnmz::{Real, Tape};
+/// let tape = Tape::new();
+/// let x = tape.vars(&[2.0, 3.0]);
+///
+/// let y = x[0] * x[1] + x[0].sin();
+///
+/// assert_eq!(y.gradient(&x), vec![3.0 + 2.0_f64.cos(), 2.0]);
+/// ```
+#[derive(Default)]
+pub struct Tape {
+    nodes: RefCell<Vec<Node>>,
+}
+
+impl Tape {
+    /// Empty tape.
+    pub fn new() -> Self {
+        Tape { nodes: RefCell::new(Vec::new()) }
+    }
+
+    /// Independent variable with the value `x`.
+    pub fn var(&self, x: f64) -> Var<'_> {
+        self.push(x, [(0, 0.0), (0, 0.0)])
+    }
+
+    /// Independent variables with the values `x`.
+    pub fn vars(&self, x: &[f64]) -> Vec<Var<'_>> {
+        x.iter().map(|&xi| self.var(xi)).collect()
+    }
+
+    /// Number of recorded operations.
+    pub fn len(&self) -> usize {
+        self.nodes.borrow().len()
+    }
+
+    /// Whether no operations are recorded.
+    pub fn is_empty(&self) -> bool {
+        self.nodes.borrow().is_empty()
+    }
+
+    /// Forget all operations, to reuse the memory for another evaluation.
+    pub fn clear(&mut self) {
+        self.nodes.get_mut().clear();
+    }
+
+    fn push(&self, value: f64, args: [(usize, f64); 2]) -> Var<'_> {
+        let mut nodes = self.nodes.borrow_mut();
+        nodes.push(Node { args });
+        Var { tape: Some(self), index: nodes.len() - 1, value }
+    }
+}
+
+/// Variable recorded on a [`Tape`].
+///
+/// Constants made by [`Real::from_f64`] do not belong to any tape and are not recorded.
+#[derive(Clone, Copy)]
+pub struct Var<'t> {
+    tape: Option<&'t Tape>,
+    index: usize,
+    value: f64,
+}
+
+impl<'t> Var<'t> {
+    /// Value.
+    pub fn value(&self) -> f64 {
+        self.value
+    }
+
+    /// Derivatives of this variable with respect to `wrt`, by one backward pass.
+    pub fn gradient(&self, wrt: &[Var<'t>]) -> Vec<f64> {
+        let tape = match self.tape {
+            Some(tape) => tape,
+            None => return vec![0.0; wrt.len()],
+        };
+        let nodes = tape.nodes.borrow();
+        let mut adjoint = vec![0.0; self.index + 1];
+        adjoint[self.index] = 1.0;
+        for i in (0..=self.index).rev() {
+            let a = adjoint[i];
+            if a != 0.0 {
+                for &(j, d) in nodes[i].args.iter() {
+                    adjoint[j] += d * a;
+                }
+            }
+        }
+        wrt.iter().map(|v| match v.tape {
+            Some(t) if std::ptr::eq(t, tape) && v.index <= self.index => adjoint[v.index],
+            _ => 0.0,
+        }).collect()
+    }
+
+    // f(u) with f' = d at u.
+    fn unary(self, value: f64, d: f64) -> Self {
+        match self.tape {
+            Some(tape) => tape.push(value, [(self.index, d), (0, 0.0)]),
+            None => Var { tape: None, index: 0, value },
+        }
+    }
+
+    // f(u, v) with partial derivatives du, dv.
+    fn binary(self, other: Self, value: f64, du: f64, dv: f64) -> Self {
+        match (self.tape, other.tape) {
+            (Some(tape), Some(other_tape)) => {
+                assert!(std::ptr::eq(tape, other_tape), "variables from different tapes");
+                tape.push(value, [(self.index, du), (other.index, dv)])
+            }
+            (Some(_), None) => self.unary(value, du),
+            (None, Some(_)) => other.unary(value, dv),
+            (None, None) => Var { tape: None, index: 0, value },
+        }
+    }
+}
+
+impl std::fmt::Debug for Var<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Var({})", self.value)
+    }
+}
+
+impl PartialEq for Var<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl PartialOrd for Var<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.value.partial_cmp(&other.value)
+    }
+}
+
+impl<'t> Add for Var<'t> {
+    type Output = Var<'t>;
+    fn add(self, o: Self) -> Self { self.binary(o, self.value + o.value, 1.0, 1.0) }
+}
+
+impl<'t> Sub for Var<'t> {
+    type Output = Var<'t>;
+    fn sub(self, o: Self) -> Self { self.binary(o, self.value - o.value, 1.0, -1.0) }
+}
+
+impl<'t> Mul for Var<'t> {
+    type Output = Var<'t>;
+    fn mul(self, o: Self) -> Self { self.binary(o, self.value * o.value, o.value, self.value) }
+}
+
+impl<'t> Div for Var<'t> {
+    type Output = Var<'t>;
+    fn div(self, o: Self) -> Self {
+        let q = self.value / o.value;
+        self.binary(o, q, 1.0 / o.value, -q / o.value)
+    }
+}
+
+impl<'t> Neg for Var<'t> {
+    type Output = Var<'t>;
+    fn neg(self) -> Self { self.unary(-self.value, -1.0) }
+}
+
+impl<'t> Add<f64> for Var<'t> {
+    type Output = Var<'t>;
+    fn add(self, c: f64) -> Self { self.unary(self.value + c, 1.0) }
+}
+
+impl<'t> Sub<f64> for Var<'t> {
+    type Output = Var<'t>;
+    fn sub(self, c: f64) -> Self { self.unary(self.value - c, 1.0) }
+}
+
+impl<'t> Mul<f64> for Var<'t> {
+    type Output = Var<'t>;
+    fn mul(self, c: f64) -> Self { self.unary(self.value * c, c) }
+}
+
+impl<'t> Div<f64> for Var<'t> {
+    type Output = Var<'t>;
+    fn div(self, c: f64) -> Self { self.unary(self.value / c, 1.0 / c) }
+}
+
+impl<'t> Add<Var<'t>> for f64 {
+    type Output = Var<'t>;
+    fn add(self, v: Var<'t>) -> Var<'t> { v + self }
+}
+
+impl<'t> Sub<Var<'t>> for f64 {
+    type Output = Var<'t>;
+    fn sub(self, v: Var<'t>) -> Var<'t> { v.unary(self - v.value, -1.0) }
+}
+
+impl<'t> Mul<Var<'t>> for f64 {
+    type Output = Var<'t>;
+    fn mul(self, v: Var<'t>) -> Var<'t> { v * self }
+}
+
+impl<'t> Div<Var<'t>> for f64 {
+    type Output = Var<'t>;
+    fn div(self, v: Var<'t>) -> Var<'t> { let q = self / v.value; v.unary(q, -q / v.value) }
+}
+
+macro_rules! impl_assign_ops {
+    ($rhs:ty) => {
+        impl<'t> AddAssign<$rhs> for Var<'t> { fn add_assign(&mut self, o: $rhs) { *self = *self + o; } }
+        impl<'t> SubAssign<$rhs> for Var<'t> { fn sub_assign(&mut self, o: $rhs) { *self = *self - o; } }
+        impl<'t> MulAssign<$rhs> for Var<'t> { fn mul_assign(&mut self, o: $rhs) { *self = *self * o; } }
+        impl<'t> DivAssign<$rhs> for Var<'t> { fn div_assign(&mut self, o: $rhs) { *self = *self / o; } }
+    };
+}
+
+impl_assign_ops!(Var<'t>);
+impl_assign_ops!(f64);
+
+impl Real for Var<'_> {
+    fn from_f64(v: f64) -> Self { Var { tape: None, index: 0, value: v } }
+    fn value(&self) -> f64 { self.value }
+
+    fn abs(self) -> Self { self.unary(self.value.abs(), if self.value < 0.0 { -1.0 } else { 1.0 }) }
+    fn sqrt(self) -> Self { let s = self.value.sqrt(); self.unary(s, 0.5 / s) }
+    fn exp(self) -> Self { let e = self.value.exp(); self.unary(e, e) }
+    fn ln(self) -> Self { self.unary(self.value.ln(), 1.0 / self.value) }
+    fn powi(self, n: i32) -> Self { self.unary(self.value.powi(n), n as f64 * self.value.powi(n - 1)) }
+    fn powf(self, p: f64) -> Self { self.unary(self.value.powf(p), p * self.value.powf(p - 1.0)) }
+    fn sin(self) -> Self { self.unary(self.value.sin(), self.value.cos()) }
+    fn cos(self) -> Self { self.unary(self.value.cos(), -self.value.sin()) }
+    fn tan(self) -> Self { let t = self.value.tan(); self.unary(t, 1.0 + t * t) }
+    fn atan(self) -> Self { self.unary(self.value.atan(), 1.0 / (1.0 + self.value * self.value)) }
+    fn sinh(self) -> Self { self.unary(self.value.sinh(), self.value.cosh()) }
+    fn cosh(self) -> Self { self.unary(self.value.cosh(), self.value.sinh()) }
+    fn tanh(self) -> Self { let t = self.value.tanh(); self.unary(t, 1.0 - t * t) }
+}
+
+/// Value and gradient of `f` at `x` by reverse mode, one forward and one backward pass.
+pub fn reverse_gradient<F>(fun: F, x: &[f64]) -> (f64, Vec<f64>)
+where
+    F: for<'t> Fn (&[Var<'t>]) -> Var<'t>
+{
+    let tape = Tape::new();
+    let xs = tape.vars(x);
+    let y = fun(&xs);
+    (y.value(), y.gradient(&xs))
+}
+
+/// Turn a function of tape variables into the `fun(x, grad) -> f(x)` form of the gradient
+/// methods, such as [`lbfgsb`](crate::lbfgsb) and [`sqp`](crate::sqp).
+///
+/// A generic objective `fn f<T: Real>(x: &[T]) -> T` is passed as the closure `|x| f(x)`,
+/// which is valid for the variables of every tape.
+///
+/// # Example
+///
+/// ```
+/// use rustamath_mnmz::{lbfgsb, with_reverse_gradient, Real};
+/// use assert_float_eq::*;
+/// fn rosenbrock<T: Real>(x: &[T]) -> T {
+///     (-x[0] + 1.0).powi(2) + (x[1] - x[0] * x[0]).powi(2) * 100.0
+/// }
+/// let fun = with_reverse_gradient(|x| rosenbrock(x));
+///
+/// let res = lbfgsb(fun, &[-1.2, 1.0], &[-2.0, -2.0], &[2.0, 2.0], 0, 1.0e-9, 0);
+///
+/// assert_float_absolute_eq!(res.xmin[0], 1.0, 1.0e-7);
+/// assert_float_absolute_eq!(res.xmin[1], 1.0, 1.0e-7);
+/// ```
+pub fn with_reverse_gradient<F>(fun: F) -> impl Fn (&[f64], &mut [f64]) -> f64
+where
+    F: for<'t> Fn (&[Var<'t>]) -> Var<'t>
+{
+    move |x, g| {
+        let (f, grad) = reverse_gradient(&fun, x);
+        g.copy_from_slice(&grad);
+        f
+    }
+}
+
+#[cfg(test)]
+#[test]
+fn test_against_forward_mode() {
+    use super::dual::dual_gradient;
+
+    fn composite<T: Real>(x: &[T]) -> T {
+        let r = (x[0] * x[0] + x[1] * x[1] + 1.0).sqrt();
+        let a = r.ln() * x[2].sin() - x[0].cos().exp() / (x[1].tanh() + 2.0);
+        let b = x[2].atan() * x[0].sinh() + x[1].cosh().powf(0.5) - (x[0] * 0.3).tan();
+        let c = T::from_f64(1.0) / (-x[2]).abs().powi(3) - x[0] / x[1] + 2.0 - x[2];
+        let mut s = a * b;
+        s += c;
+        s *= T::from_f64(0.5);
+        s
+    }
+
+    let x = [0.7, -1.3, 0.4];
+    let (f, grad) = reverse_gradient(|x| composite(x), &x);
+    let (f_dual, grad_dual) = dual_gradient(composite, &x);
+    println!("f: {} grad: {:?} forward: {:?}", f, grad, grad_dual);
+    assert_eq!(f, f_dual);
+    for (g, gd) in grad.iter().zip(grad_dual.iter()) {
+        assert_float_relative_eq!(*g, *gd, 1.0e-14);
+    }
+
+    // Constants and variables of other tapes have no derivative.
+    let tape = Tape::new();
+    let other = Tape::new();
+    let (u, v) = (tape.var(2.0), other.var(3.0));
+    let y = u * u + Var::from_f64(5.0);
+    assert_eq!(y.gradient(&[u, v, Var::from_f64(1.0)]), vec![4.0, 0.0, 0.0]);
+    assert_eq!(Var::from_f64(1.0).gradient(&[u]), vec![0.0]);
+}
+
+#[cfg(test)]
+#[test]
+fn test_large_rosenbrock() {
+    use super::lbfgsb;
+
+    // Extended Rosenbrock function, independent pairs.
+    fn rosenbrock<T: Real>(x: &[T]) -> T {
+        let mut f = T::from_f64(0.0);
+        for i in (0..x.len()).step_by(2) {
+            f += (-x[i] + 1.0).powi(2) + (x[i + 1] - x[i] * x[i]).powi(2) * 100.0;
+        }
+        f
+    }
+
+    let n = 10_000;
+    let x: Vec<f64> = (0..n).map(|i| if i % 2 == 0 { -1.2 } else { 1.0 }).collect();
+    let mut tape = Tape::new();
+    let xs = tape.vars(&x);
+    let y = rosenbrock(&xs);
+    let grad = y.gradient(&xs);
+    assert_eq!(y.value(), rosenbrock(&x));
+    assert_float_absolute_eq!(grad[0], -215.6, 1.0e-10);
+    assert_float_absolute_eq!(grad[n - 1], -88.0, 1.0e-10);
+    println!("tape: {} operations for n = {}", tape.len(), n);
+    tape.clear();
+    assert!(tape.is_empty());
+
+    let res = lbfgsb(with_reverse_gradient(|x| rosenbrock(x)), &x,
+        &vec![f64::NEG_INFINITY; n], &vec![f64::INFINITY; n], 0, 1.0e-8, 0);
+    println!("f: {} iterations: {} evaluations: {}", res.fmin, res.nr_iterations, res.nr_evaluations);
+    assert!(res.converged);
+    for xi in res.xmin.iter() {
+        assert_float_absolute_eq!(*xi, 1.0, 1.0e-6);
+    }
+}
+
+#[cfg(test)]
+#[test]
+#[should_panic(expected = "variables from different tapes")]
+fn test_different_tapes() {
+    let tape1 = Tape::new();
+    let tape2 = Tape::new();
+    let _ = tape1.var(1.0) * tape2.var(2.0);
+}